@@ -1,6 +1,12 @@
 use std::mem::swap;
 
-use crate::{rays::Ray, vectors::Point3};
+use crate::{
+    rays::Ray,
+    vectors::{Point3, Vec3},
+};
+
+/// Thickness given to the boxes of flat primitives with `AAAB::pad`
+pub const B_BOX_PADDING: f32 = 1e-4;
 
 #[derive(Clone, Copy)]
 pub struct AAAB {
@@ -21,25 +27,90 @@ impl AAAB {
         self.max
     }
 
+    pub fn centroid(&self) -> Point3<f32> {
+        0.5 * (self.min + self.max)
+    }
+
+    pub fn surface_area(&self) -> f32 {
+        let size = self.max - self.min;
+
+        2.0 * (size.x() * size.y() + size.y() * size.z() + size.z() * size.x())
+    }
+
+    /// Grows the box along any axis thinner than `delta`, so flat primitives
+    /// (axis-aligned triangles, rectangles) still get hit by `is_in`.
+    pub fn pad(&self, delta: f32) -> Self {
+        let pad_axis = |min: f32, max: f32| {
+            if max - min < delta {
+                (min - delta / 2.0, max + delta / 2.0)
+            } else {
+                (min, max)
+            }
+        };
+
+        let (min_x, max_x) = pad_axis(self.min.x(), self.max.x());
+        let (min_y, max_y) = pad_axis(self.min.y(), self.max.y());
+        let (min_z, max_z) = pad_axis(self.min.z(), self.max.z());
+
+        Self::new(
+            Point3::new(min_x, min_y, min_z),
+            Point3::new(max_x, max_y, max_z),
+        )
+    }
+
+    pub fn contains(&self, point: &Point3<f32>) -> bool {
+        (0..3).all(|dim| {
+            let value = point.get(dim).unwrap();
+            self.min.get(dim).unwrap() <= value && value <= self.max.get(dim).unwrap()
+        })
+    }
+
     pub fn is_in(&self, ray: &Ray, t_min: f32, t_max: f32) -> bool {
+        self.ray_range(ray, t_min, t_max).is_some()
+    }
+
+    /// Part of `[t_min, t_max]` where the ray is inside the box.
+    pub fn ray_range(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<(f32, f32)> {
+        let direction = ray.direction();
+        let inverse_direction = Vec3::new(
+            1.0 / direction.x(),
+            1.0 / direction.y(),
+            1.0 / direction.z(),
+        );
+
+        self.range_with_inverse(&ray.origin(), &inverse_direction, t_min, t_max)
+    }
+
+    /// `ray_range` with the reciprocal of the ray direction computed once by
+    /// the caller, for traversals that test many boxes against the same ray.
+    pub fn range_with_inverse(
+        &self,
+        origin: &Point3<f32>,
+        inverse_direction: &Vec3<f32>,
+        t_min: f32,
+        t_max: f32,
+    ) -> Option<(f32, f32)> {
+        let mut t_min = t_min;
+        let mut t_max = t_max;
+
         for dim in 0..3 {
-            let inv_d = 1.0 / ray.direction().get(dim).unwrap();
-            let mut t0 = (self.min().get(dim).unwrap() - ray.origin().get(dim).unwrap()) * inv_d;
-            let mut t1 = (self.max().get(dim).unwrap() - ray.origin().get(dim).unwrap()) * inv_d;
+            let inv_d = inverse_direction.get(dim).unwrap();
+            let mut t0 = (self.min().get(dim).unwrap() - origin.get(dim).unwrap()) * inv_d;
+            let mut t1 = (self.max().get(dim).unwrap() - origin.get(dim).unwrap()) * inv_d;
 
             if inv_d < 0.0 {
                 swap(&mut t0, &mut t1);
             }
 
-            let t_min = if t0 > t_min { t0 } else { t_min };
-            let t_max = if t1 < t_max { t1 } else { t_max };
+            t_min = if t0 > t_min { t0 } else { t_min };
+            t_max = if t1 < t_max { t1 } else { t_max };
 
             if t_min >= t_max {
-                return false;
+                return None;
             }
         }
 
-        true
+        Some((t_min, t_max))
     }
 
     pub fn new_surrounding_box(box0: Self, box1: Self) -> Self {
@@ -57,4 +128,40 @@ impl AAAB {
 
         Self::new(small, big)
     }
+
+    pub fn surrounding_points(points: &[Point3<f32>]) -> Self {
+        let first = AAAB::new(points[0], points[0]);
+
+        points[1..].iter().fold(first, |b_box, point| {
+            AAAB::new_surrounding_box(b_box, AAAB::new(*point, *point))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vectors::Vec3;
+
+    fn unit_box() -> AAAB {
+        AAAB::new(Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 1.0, 1.0))
+    }
+
+    #[test]
+    fn test_ray_crossing_slabs_at_disjoint_times_misses() {
+        // Inside the x slab for t in [2, 3] and the y slab for t in [0, 1],
+        // so it passes beside the box without ever being in all three slabs
+        let ray = Ray::new(Point3::new(-2.0, 0.0, 0.5), Vec3::new(1.0, 1.0, 0.0), 0.0);
+
+        assert!(!unit_box().is_in(&ray, 0.001, f32::INFINITY));
+    }
+
+    #[test]
+    fn test_ray_range() {
+        let ray = Ray::new(Point3::new(-1.0, 0.5, 0.5), Vec3::new(1.0, 0.0, 0.0), 0.0);
+
+        assert_eq!(unit_box().ray_range(&ray, 0.001, f32::INFINITY), Some((1.0, 2.0)));
+        assert_eq!(unit_box().ray_range(&ray, 0.001, 1.5), Some((1.0, 1.5)));
+        assert_eq!(unit_box().ray_range(&ray, 0.001, 0.5), None);
+    }
 }
@@ -0,0 +1,135 @@
+use crate::rays::Color;
+
+// Keeps the error estimate of very dark pixels from demanding endless samples
+const MIN_LUMINANCE: f32 = 0.01;
+
+/// Per pixel sample budget. Sampling stops once `min_samples` have been taken
+/// and the standard error of the pixel's luminance drops below `threshold`
+/// relative to its mean, or when `max_samples` is reached.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AdaptiveSampling {
+    pub min_samples: u32,
+    pub max_samples: u32,
+    pub threshold: f32,
+}
+
+impl AdaptiveSampling {
+    pub fn new(min_samples: u32, max_samples: u32, threshold: f32) -> Self {
+        let min_samples = min_samples.max(2);
+
+        Self {
+            min_samples,
+            max_samples: max_samples.max(min_samples),
+            threshold,
+        }
+    }
+
+    /// Always takes exactly `samples` samples.
+    pub fn fixed(samples: u32) -> Self {
+        Self {
+            min_samples: samples,
+            max_samples: samples,
+            threshold: 0.0,
+        }
+    }
+}
+
+/// Running mean of a pixel's samples, along with the variance of their
+/// luminance (Welford's algorithm).
+#[derive(Clone, Copy, Default)]
+pub struct PixelEstimate {
+    count: u32,
+    mean: Color,
+    mean_luminance: f32,
+    m2: f32,
+}
+
+impl PixelEstimate {
+    pub fn add(&mut self, sample: Color) {
+        self.count += 1;
+        let n = self.count as f32;
+        self.mean += (sample - self.mean) / n;
+
+        let luminance = luminance(sample);
+        let delta = luminance - self.mean_luminance;
+        self.mean_luminance += delta / n;
+        self.m2 += delta * (luminance - self.mean_luminance);
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn mean(&self) -> Color {
+        self.mean
+    }
+
+    pub fn variance(&self) -> f32 {
+        if self.count < 2 {
+            return f32::INFINITY;
+        }
+
+        self.m2 / (self.count - 1) as f32
+    }
+
+    /// Standard error of the mean luminance relative to the mean itself
+    pub fn relative_error(&self) -> f32 {
+        let standard_error = (self.variance() / self.count as f32).sqrt();
+
+        standard_error / self.mean_luminance.max(MIN_LUMINANCE)
+    }
+
+    pub fn is_done(&self, sampling: &AdaptiveSampling) -> bool {
+        if self.count < sampling.min_samples {
+            return false;
+        }
+
+        self.count >= sampling.max_samples || self.relative_error() < sampling.threshold
+    }
+}
+
+fn luminance(color: Color) -> f32 {
+    0.2126 * color.x() + 0.7152 * color.y() + 0.0722 * color.z()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flat_pixel_converges_at_min_samples() {
+        let sampling = AdaptiveSampling::new(4, 64, 0.05);
+        let mut estimate = PixelEstimate::default();
+
+        while !estimate.is_done(&sampling) {
+            estimate.add(Color::new(0.5, 0.7, 1.0));
+        }
+
+        assert_eq!(estimate.count(), 4);
+        assert_eq!(estimate.mean(), Color::new(0.5, 0.7, 1.0));
+    }
+
+    #[test]
+    fn test_noisy_pixel_uses_max_samples() {
+        let sampling = AdaptiveSampling::new(4, 64, 0.001);
+        let mut estimate = PixelEstimate::default();
+
+        let mut i = 0;
+        while !estimate.is_done(&sampling) {
+            let value = (i % 2) as f32;
+            estimate.add(Color::new(value, value, value));
+            i += 1;
+        }
+
+        assert_eq!(estimate.count(), 64);
+    }
+
+    #[test]
+    fn test_fixed_sampling() {
+        let sampling = AdaptiveSampling::fixed(1);
+        let mut estimate = PixelEstimate::default();
+        estimate.add(Color::new(1.0, 1.0, 1.0));
+
+        assert!(estimate.is_done(&sampling));
+    }
+}
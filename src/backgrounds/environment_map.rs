@@ -0,0 +1,166 @@
+use crate::{
+    error::RenderError,
+    framebuffer::linear_pixels,
+    rays::{Color, Ray},
+};
+use image::{
+    codecs::hdr::HdrDecoder,
+    error::{ImageError, ParameterError, ParameterErrorKind},
+};
+use std::{f32::consts::PI, fs::File, io::BufReader, path::Path};
+
+use super::Background;
+
+/// Equirectangular (latitude-longitude) environment map. The middle of the
+/// image looks towards -z, the top row is straight up.
+pub struct EnvironmentMap {
+    width: u32,
+    height: u32,
+    pixels: Vec<Color>,
+    intensity: f32,
+    rotation: f32,
+}
+
+impl EnvironmentMap {
+    /// Fails if the map is empty or `pixels` does not hold `width * height`
+    /// colors.
+    pub fn new(width: u32, height: u32, pixels: Vec<Color>) -> Result<Self, RenderError> {
+        let kind = if width == 0 || height == 0 {
+            ParameterErrorKind::Generic("environment map has no pixels".to_string())
+        } else if pixels.len() != width as usize * height as usize {
+            ParameterErrorKind::DimensionMismatch
+        } else {
+            return Ok(Self {
+                width,
+                height,
+                pixels,
+                intensity: 1.0,
+                rotation: 0.0,
+            });
+        };
+
+        Err(RenderError::Decoding(ImageError::Parameter(
+            ParameterError::from_kind(kind),
+        )))
+    }
+
+    /// Loads a Radiance `.hdr` file as is, any other image format is treated
+    /// as sRGB and converted to linear colors.
+    pub fn load(path: &dyn AsRef<Path>) -> Result<Self, RenderError> {
+        let path = path.as_ref();
+        let is_hdr = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("hdr"));
+
+        if is_hdr {
+            let reader = BufReader::new(File::open(path)?);
+            let decoder = HdrDecoder::new(reader).map_err(RenderError::Decoding)?;
+            let metadata = decoder.metadata();
+            let pixels = decoder
+                .read_image_hdr()
+                .map_err(RenderError::Decoding)?
+                .into_iter()
+                .map(|pixel| Color::new(pixel[0], pixel[1], pixel[2]))
+                .collect();
+
+            return Self::new(metadata.width, metadata.height, pixels);
+        }
+
+        let image = image::open(path)
+            .map_err(RenderError::Decoding)?
+            .into_rgb8();
+        let pixels = linear_pixels(&image);
+
+        Self::new(image.width(), image.height(), pixels)
+    }
+
+    pub fn set_intensity(&mut self, intensity: f32) {
+        self.intensity = intensity;
+    }
+
+    /// Rotation around the vertical axis, in degrees
+    pub fn set_rotation(&mut self, rotation: f32) {
+        self.rotation = rotation.to_radians();
+    }
+
+    fn pixel(&self, x: u32, y: u32) -> Color {
+        self.pixels[(y * self.width + x) as usize]
+    }
+}
+
+impl Background for EnvironmentMap {
+    fn value(&self, ray: &Ray) -> Color {
+        let direction = ray.direction().unit_vector();
+
+        let phi = direction.x().atan2(-direction.z()) + self.rotation;
+        let theta = direction.y().clamp(-1.0, 1.0).acos();
+
+        let u = (phi / (2.0 * PI) + 0.5).rem_euclid(1.0);
+        let v = theta / PI;
+
+        // Bilinear filtering, wrapping around horizontally
+        let x = u * self.width as f32 - 0.5;
+        let y = (v * self.height as f32 - 0.5).clamp(0.0, (self.height - 1) as f32);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+
+        let x0 = (x0 as i64).rem_euclid(self.width as i64) as u32;
+        let x1 = (x0 + 1) % self.width;
+        let y0 = y0 as u32;
+        let y1 = (y0 + 1).min(self.height - 1);
+
+        let top = (1.0 - tx) * self.pixel(x0, y0) + tx * self.pixel(x1, y0);
+        let bottom = (1.0 - tx) * self.pixel(x0, y1) + tx * self.pixel(x1, y1);
+
+        self.intensity * ((1.0 - ty) * top + ty * bottom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vectors::{Point3, Vec3};
+
+    // Top half white, bottom half black
+    fn half_lit_map() -> EnvironmentMap {
+        let pixels = (0..8 * 4)
+            .map(|i| {
+                if i < 16 {
+                    Color::new(1.0, 1.0, 1.0)
+                } else {
+                    Color::default()
+                }
+            })
+            .collect();
+
+        EnvironmentMap::new(8, 4, pixels).unwrap()
+    }
+
+    #[test]
+    fn test_up_and_down_lookups() {
+        let map = half_lit_map();
+        let origin = Point3::default();
+
+        let up = map.value(&Ray::new(origin, Vec3::new(0.0, 1.0, 0.0), 0.0));
+        let down = map.value(&Ray::new(origin, Vec3::new(0.0, -1.0, 0.0), 0.0));
+
+        assert_eq!(up, Color::new(1.0, 1.0, 1.0));
+        assert_eq!(down, Color::default());
+    }
+
+    #[test]
+    fn test_intensity() {
+        let mut map = half_lit_map();
+        map.set_intensity(2.0);
+
+        let up = map.value(&Ray::new(Point3::default(), Vec3::new(0.1, 1.0, 0.0), 0.0));
+
+        assert_eq!(up, Color::new(2.0, 2.0, 2.0));
+    }
+
+    #[test]
+    fn test_invalid_sizes() {
+        assert!(EnvironmentMap::new(0, 0, Vec::new()).is_err());
+        assert!(EnvironmentMap::new(4, 2, vec![Color::default(); 7]).is_err());
+    }
+}
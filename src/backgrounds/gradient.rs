@@ -0,0 +1,31 @@
+use crate::rays::{Color, Ray};
+
+use super::Background;
+
+/// Vertical blend between two colors, from straight down to straight up
+pub struct GradientBackground {
+    bottom: Color,
+    top: Color,
+}
+
+impl GradientBackground {
+    pub fn new(bottom: Color, top: Color) -> Self {
+        Self { bottom, top }
+    }
+}
+
+impl Default for GradientBackground {
+    /// White to light blue sky
+    fn default() -> Self {
+        Self::new(Color::new(1.0, 1.0, 1.0), Color::new(0.5, 0.7, 1.0))
+    }
+}
+
+impl Background for GradientBackground {
+    fn value(&self, ray: &Ray) -> Color {
+        let unit_direction = ray.direction().unit_vector();
+        let t = 0.5 * (unit_direction.y() + 1.0);
+
+        (1.0 - t) * self.bottom + t * self.top
+    }
+}
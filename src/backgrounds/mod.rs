@@ -0,0 +1,11 @@
+pub mod environment_map;
+pub mod gradient;
+pub mod sky;
+pub mod solid;
+
+use crate::rays::{Color, Ray};
+
+/// Light coming from rays that leave the scene without hitting anything
+pub trait Background: Send + Sync {
+    fn value(&self, ray: &Ray) -> Color;
+}
@@ -0,0 +1,80 @@
+use crate::{
+    rays::{Color, Ray},
+    vectors::Vec3,
+};
+
+use super::Background;
+
+// Sharpness of the glow around the sun
+const GLOW_EXPONENT: i32 = 64;
+
+/// Procedural daylight sky: a zenith to horizon gradient, a darker ground
+/// below the horizon and a sun disc with a soft glow around it.
+pub struct SkyBackground {
+    sun_direction: Vec3<f32>,
+    sun_color: Color,
+    sun_intensity: f32,
+    cos_sun_radius: f32,
+    zenith_color: Color,
+    horizon_color: Color,
+    ground_color: Color,
+}
+
+impl SkyBackground {
+    /// `sun_elevation` and `sun_azimuth` are in degrees, azimuth 0 points to -z
+    pub fn new(sun_elevation: f32, sun_azimuth: f32) -> Self {
+        let (elevation, azimuth) = (sun_elevation.to_radians(), sun_azimuth.to_radians());
+        let sun_direction = Vec3::new(
+            elevation.cos() * azimuth.sin(),
+            elevation.sin(),
+            -elevation.cos() * azimuth.cos(),
+        );
+
+        Self {
+            sun_direction,
+            sun_color: Color::new(1.0, 0.95, 0.85),
+            sun_intensity: 50.0,
+            cos_sun_radius: 0.5_f32.to_radians().cos(),
+            zenith_color: Color::new(0.25, 0.45, 0.9),
+            horizon_color: Color::new(0.85, 0.9, 1.0),
+            ground_color: Color::new(0.35, 0.3, 0.25),
+        }
+    }
+
+    pub fn set_sun(&mut self, color: Color, intensity: f32, angular_radius: f32) {
+        self.sun_color = color;
+        self.sun_intensity = intensity;
+        self.cos_sun_radius = angular_radius.to_radians().cos();
+    }
+
+    pub fn set_colors(&mut self, zenith: Color, horizon: Color, ground: Color) {
+        self.zenith_color = zenith;
+        self.horizon_color = horizon;
+        self.ground_color = ground;
+    }
+}
+
+impl Background for SkyBackground {
+    fn value(&self, ray: &Ray) -> Color {
+        let direction = ray.direction().unit_vector();
+        let height = direction.y();
+
+        if height < 0.0 {
+            // Quickly fade from the horizon haze into the ground
+            let t = (-height * 10.0).min(1.0);
+            return (1.0 - t) * self.horizon_color + t * self.ground_color;
+        }
+
+        let t = height.sqrt();
+        let mut color = (1.0 - t) * self.horizon_color + t * self.zenith_color;
+
+        let cos_sun = direction.dot(&self.sun_direction);
+        if cos_sun > self.cos_sun_radius {
+            color += self.sun_intensity * self.sun_color;
+        } else if cos_sun > 0.0 {
+            color += cos_sun.powi(GLOW_EXPONENT) * self.sun_color;
+        }
+
+        color
+    }
+}
@@ -0,0 +1,30 @@
+use crate::rays::{Color, Ray};
+
+use super::Background;
+
+pub struct SolidBackground {
+    color: Color,
+}
+
+impl SolidBackground {
+    pub fn new(color: Color) -> Self {
+        Self { color }
+    }
+
+    /// No light at all, for scenes lit only by emissive materials
+    pub fn black() -> Self {
+        Self::new(Color::default())
+    }
+}
+
+impl From<Color> for SolidBackground {
+    fn from(color: Color) -> Self {
+        Self::new(color)
+    }
+}
+
+impl Background for SolidBackground {
+    fn value(&self, _ray: &Ray) -> Color {
+        self.color
+    }
+}
@@ -1,31 +1,33 @@
 #![warn(clippy::all)]
-use rand::{thread_rng, Rng};
 use raytracer::bvh::BVHNode;
 use raytracer::hit::HitList;
 use raytracer::materials::{dielectric::Dielectric, lambertian::Lambertian, metal::Metal};
 use raytracer::objects::{moving_sphere::MovingSphere, sphere::Sphere};
 use raytracer::rays::Color;
+use raytracer::sampling::{independent::IndependentSampler, Sampler, SamplerKind};
 use raytracer::vectors::{Point3, Vec3};
-use raytracer::{camera::Camera, Raytracer};
+use raytracer::{adaptive::AdaptiveSampling, camera::Camera, Raytracer};
 use std::sync::Arc;
 use std::time::{Instant, SystemTime};
 
 const FILENAME: &str = "balls";
 const ASPECT_RATIO: f32 = 16.0 / 9.0;
 const SAMPLE_SIZE: u32 = 100;
+const MIN_SAMPLE_SIZE: u32 = 16;
+const MAX_SAMPLE_SIZE: u32 = 400;
+const NOISE_THRESHOLD: f32 = 0.02;
+const SCENE_SEED: u64 = 2021;
 
 fn main() {
     // Start timer
     let now = Instant::now();
 
-    let filename = format!(
-        "output/{}_{}.png",
-        FILENAME,
-        SystemTime::now()
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .unwrap()
-            .as_secs()
-    );
+    let timestamp = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let filename = format!("output/{}_{}.png", FILENAME, timestamp);
+    let heatmap_filename = format!("output/{}_{}_samples.png", FILENAME, timestamp);
 
     // Dimensions
     const WIDTH: u32 = 640;
@@ -48,19 +50,32 @@ fn main() {
         focus_distance,
     );
 
-    let raytracer = Raytracer::new(WIDTH, HEIGHT, camera, SAMPLE_SIZE);
+    let mut raytracer = Raytracer::new(WIDTH, HEIGHT, camera, SAMPLE_SIZE);
+    raytracer.set_sampler(SamplerKind::Sobol);
+    raytracer.set_adaptive_sampling(AdaptiveSampling::new(
+        MIN_SAMPLE_SIZE,
+        MAX_SAMPLE_SIZE,
+        NOISE_THRESHOLD,
+    ));
 
     // Scene
-    let mut list = generate_random_scene();
-    let scene = BVHNode::new(&mut list, 0.0, 1.0);
+    let list = generate_random_scene();
+    let scene = BVHNode::new(&list, 0.0, 1.0);
+    println!("BVH: {}", scene.report());
+
+    let frame = raytracer.render_to_buffer(&scene);
 
-    raytracer.render(scene, &filename);
+    frame.save(&filename).expect("Could not save image");
+    frame
+        .sample_heatmap()
+        .save(&heatmap_filename)
+        .expect("Could not save sample heatmap");
 
     println!("Finished in {} ms", now.elapsed().as_millis());
 }
 
 fn generate_random_scene() -> HitList {
-    let mut rng = thread_rng();
+    let mut sampler = IndependentSampler::new(SCENE_SEED);
     let mut scene = HitList::new();
 
     let ground_mat = Lambertian::new(Color::new(0.5, 0.5, 0.5));
@@ -73,19 +88,20 @@ fn generate_random_scene() -> HitList {
 
     for i in -11..11 {
         for j in -11..11 {
-            let random: f32 = rng.gen();
+            let random = sampler.next_1d();
 
             let center = Point3::new(
-                i as f32 + 0.9 * rng.gen::<f32>(),
+                i as f32 + 0.9 * sampler.next_1d(),
                 0.2,
-                j as f32 + 0.9 * rng.gen::<f32>(),
+                j as f32 + 0.9 * sampler.next_1d(),
             );
 
             if (center - Point3::new(4.0, 2.0, 0.0)).norm() > 0.9 {
                 if random < 0.8 {
-                    let albedo = Color::new_random(0.0, 1.0) * Color::new_random(0.0, 1.0);
+                    let albedo = Color::new_random(&mut sampler, 0.0, 1.0)
+                        * Color::new_random(&mut sampler, 0.0, 1.0);
                     let material = Arc::new(Lambertian::new(albedo));
-                    let center_end: Point3<f32> = center + Vec3::new(0.0, rng.gen(), 0.0);
+                    let center_end: Point3<f32> = center + Vec3::new(0.0, sampler.next_1d(), 0.0);
                     let sphere = MovingSphere {
                         center_start: center,
                         center_end,
@@ -96,8 +112,8 @@ fn generate_random_scene() -> HitList {
                     };
                     scene.add(Arc::new(sphere));
                 } else if random < 0.95 {
-                    let albedo = Color::new_random(0.5, 1.0);
-                    let fuzz = rng.gen_range(0.0..0.5);
+                    let albedo = Color::new_random(&mut sampler, 0.5, 1.0);
+                    let fuzz = 0.5 * sampler.next_1d();
                     let material = Arc::new(Metal::new(albedo, fuzz));
                     let sphere = Sphere {
                         center,
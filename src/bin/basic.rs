@@ -1,9 +1,11 @@
 use raytracer::{
+    bvh::BVHNode,
     camera::Camera,
     hit::HitList,
     materials::{dielectric::Dielectric, lambertian::Lambertian, metal::Metal},
     objects::{plane::Plane, sphere::Sphere},
     rays::Color,
+    sampling::SamplerKind,
     vectors::{Point3, Vec3},
     Raytracer,
 };
@@ -15,6 +17,7 @@ use std::{
 const FILENAME: &str = "basic";
 const ASPECT_RATIO: f32 = 16.0 / 9.0;
 const SAMPLE_SIZE: u32 = 500;
+const PREVIEW_EVERY: u32 = 10;
 
 fn main() {
     let now = Instant::now();
@@ -50,7 +53,7 @@ fn main() {
     );
 
     // Scene
-    let mut scene = HitList::new();
+    let mut list = HitList::new();
 
     let mat_1 = Lambertian::new(Color::new(0.8, 0.8, 0.0));
     let sphere1 = Sphere {
@@ -83,15 +86,30 @@ fn main() {
         radius: -0.05,
         material: Arc::new(glass_mat),
     };
-    scene.add(Arc::new(sphere1));
-    scene.add(Arc::new(ground));
-    scene.add(Arc::new(sphere2));
-    scene.add(Arc::new(metal));
-    scene.add(Arc::new(crystal_ball));
+    list.add(Arc::new(sphere1));
+    list.add(Arc::new(ground));
+    list.add(Arc::new(sphere2));
+    list.add(Arc::new(metal));
+    list.add(Arc::new(crystal_ball));
 
-    let raytracer = Raytracer::new(WIDTH, HEIGHT, camera, SAMPLE_SIZE);
+    // The ground plane has no bounding box and stays outside the hierarchy
+    let scene = BVHNode::new(&list, 0.0, 1.0);
 
-    raytracer.render(scene, &filename);
+    let mut raytracer = Raytracer::new(WIDTH, HEIGHT, camera, SAMPLE_SIZE);
+    raytracer.set_sampler(SamplerKind::Sobol);
+
+    // Overwrite the same preview file every few passes, so it can be watched in an image viewer
+    let preview_filename = format!("output/{}_preview.png", FILENAME);
+    let frame = raytracer.render_progressive(&scene, |pass, frame| {
+        if pass % PREVIEW_EVERY == 0 {
+            frame
+                .save(&preview_filename)
+                .expect("Could not save preview");
+        }
+        true
+    });
+
+    frame.save(&filename).expect("Could not save image");
 
     println!("Finished in {} ms", now.elapsed().as_millis());
 }
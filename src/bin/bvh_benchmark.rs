@@ -0,0 +1,74 @@
+use raytracer::{
+    bvh::{BVHBuilder, FlatBVH},
+    hit::{HitList, Hittable},
+    materials::lambertian::Lambertian,
+    objects::sphere::Sphere,
+    rays::{Color, Ray},
+    sampling::{independent::IndependentSampler, Sampler},
+    vectors::Vec3,
+};
+use std::{sync::Arc, time::Instant};
+
+const SPHERE_COUNT: usize = 200_000;
+const RAY_COUNT: usize = 200_000;
+
+// Times serial and parallel builds, then closest-hit queries of the recursive
+// tree against its flattened copy
+fn main() {
+    let mut sampler = IndependentSampler::new(1);
+    let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+
+    let mut list = HitList::new();
+    for _ in 0..SPHERE_COUNT {
+        list.add(Arc::new(Sphere {
+            center: Vec3::new_random(&mut sampler, -100.0, 100.0),
+            radius: 0.2 + 0.3 * sampler.next_1d(),
+            material: material.clone(),
+        }));
+    }
+
+    let rays: Vec<Ray> = (0..RAY_COUNT)
+        .map(|_| {
+            let origin = Vec3::new_random(&mut sampler, -150.0, 150.0);
+            let target = Vec3::new_random(&mut sampler, -50.0, 50.0);
+            Ray::new(origin, target - origin, 0.0)
+        })
+        .collect();
+
+    let mut builder = BVHBuilder::new(0.0, 1.0);
+    builder.set_thread_count(1);
+    let (_, serial_stats) = builder.build_with_stats(&list);
+    println!("Serial build: {}", serial_stats);
+
+    builder.set_thread_count(num_cpus::get());
+    let (tree, stats) = builder.build_with_stats(&list);
+    println!("Parallel build: {}", stats);
+    println!("Hierarchy: {}", tree.report());
+
+    let now = Instant::now();
+    let flat = FlatBVH::from_tree(&tree, &builder);
+    println!("Flattened in {} ms", now.elapsed().as_millis());
+
+    let tree_hits = trace("Tree", &tree, &rays);
+    let flat_hits = trace("Flat", &flat, &rays);
+    assert_eq!(tree_hits, flat_hits, "Both hierarchies should agree");
+}
+
+fn trace(name: &str, scene: &dyn Hittable, rays: &[Ray]) -> usize {
+    let now = Instant::now();
+    let hits = rays
+        .iter()
+        .filter(|ray| scene.hit(ray, 0.001, f32::INFINITY).is_some())
+        .count();
+    let elapsed = now.elapsed();
+
+    println!(
+        "{}: {} hits in {} ms, {:.3} Mrays/s",
+        name,
+        hits,
+        elapsed.as_millis(),
+        rays.len() as f64 / elapsed.as_secs_f64() / 1e6
+    );
+
+    hits
+}
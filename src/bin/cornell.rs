@@ -0,0 +1,118 @@
+use raytracer::{
+    backgrounds::solid::SolidBackground,
+    bvh::BVHNode,
+    camera::Camera,
+    hit::HitList,
+    materials::{diffuse_light::DiffuseLight, lambertian::Lambertian},
+    objects::{
+        box_shape::BoxShape,
+        rect::{XYRect, XZRect, YZRect},
+        transformed::Transformed,
+    },
+    rays::Color,
+    sampling::SamplerKind,
+    vectors::{transform::Transform, Point3, Vec3},
+    Raytracer,
+};
+use std::{
+    sync::Arc,
+    time::{Instant, SystemTime},
+};
+
+const FILENAME: &str = "cornell";
+const ASPECT_RATIO: f32 = 1.0;
+const SAMPLE_SIZE: u32 = 500;
+
+fn main() {
+    let now = Instant::now();
+
+    let filename = format!(
+        "output/{}_{}.png",
+        FILENAME,
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    );
+
+    // Dimensions
+    const WIDTH: u32 = 600;
+    const HEIGHT: u32 = (WIDTH as f32 / ASPECT_RATIO) as u32;
+
+    // Camera
+    let lookfrom = Point3::new(278.0, 278.0, -800.0);
+    let lookat = Point3::new(278.0, 278.0, 0.0);
+    let vup = Vec3::new(0.0, 1.0, 0.0);
+
+    let camera = Camera::new(lookfrom, lookat, vup, 40.0, ASPECT_RATIO, 0.0, 10.0);
+
+    // Scene
+    let red = Arc::new(Lambertian::new(Color::new(0.65, 0.05, 0.05)));
+    let white = Arc::new(Lambertian::new(Color::new(0.73, 0.73, 0.73)));
+    let green = Arc::new(Lambertian::new(Color::new(0.12, 0.45, 0.15)));
+    let light = Arc::new(DiffuseLight::new(Color::new(1.0, 1.0, 1.0), 15.0));
+
+    let mut list = HitList::new();
+    list.add(Arc::new(YZRect::new(0.0, 555.0, 0.0, 555.0, 555.0, green)));
+    list.add(Arc::new(YZRect::new(0.0, 555.0, 0.0, 555.0, 0.0, red)));
+    list.add(Arc::new(XZRect {
+        flip_normal: true,
+        ..XZRect::new(213.0, 343.0, 227.0, 332.0, 554.0, light)
+    }));
+    list.add(Arc::new(XZRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        0.0,
+        white.clone(),
+    )));
+    list.add(Arc::new(XZRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        555.0,
+        white.clone(),
+    )));
+    list.add(Arc::new(XYRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        555.0,
+        white.clone(),
+    )));
+
+    let tall_box = BoxShape::new(
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(165.0, 330.0, 165.0),
+        white.clone(),
+    );
+    list.add(Arc::new(Transformed::new(
+        tall_box,
+        Transform::rotation_y(15.0).then(&Transform::translation(Vec3::new(265.0, 0.0, 295.0))),
+    )));
+
+    let short_box = BoxShape::new(
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(165.0, 165.0, 165.0),
+        white,
+    );
+    list.add(Arc::new(Transformed::new(
+        short_box,
+        Transform::rotation_y(-18.0).then(&Transform::translation(Vec3::new(130.0, 0.0, 65.0))),
+    )));
+
+    let scene = BVHNode::new(&list, 0.0, 1.0);
+
+    let mut raytracer = Raytracer::new(WIDTH, HEIGHT, camera, SAMPLE_SIZE);
+    raytracer.set_sampler(SamplerKind::Sobol);
+    raytracer.set_background(Arc::new(SolidBackground::black()));
+
+    raytracer
+        .render(&scene, &filename)
+        .expect("Could not save image");
+
+    println!("Finished in {} ms", now.elapsed().as_millis());
+}
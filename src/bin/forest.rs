@@ -0,0 +1,124 @@
+use raytracer::{
+    backgrounds::sky::SkyBackground,
+    camera::Camera,
+    hit::{HitList, Hittable},
+    materials::lambertian::Lambertian,
+    objects::{
+        instance::{prototype, Instance, InstanceTree},
+        sphere::Sphere,
+    },
+    rays::Color,
+    sampling::{independent::IndependentSampler, Sampler, SamplerKind},
+    vectors::{transform::Transform, Point3, Vec3},
+    Raytracer,
+};
+use std::{
+    sync::Arc,
+    time::{Instant, SystemTime},
+};
+
+const FILENAME: &str = "forest";
+const ASPECT_RATIO: f32 = 16.0 / 9.0;
+const SAMPLE_SIZE: u32 = 64;
+const FOREST_SIZE: i32 = 25;
+const SCENE_SEED: u64 = 7;
+
+fn main() {
+    let now = Instant::now();
+
+    let filename = format!(
+        "output/{}_{}.png",
+        FILENAME,
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    );
+
+    // Dimensions
+    const WIDTH: u32 = 800;
+    const HEIGHT: u32 = (WIDTH as f32 / ASPECT_RATIO) as u32;
+
+    // Camera
+    let lookfrom = Point3::new(0.0, 6.0, 30.0);
+    let lookat = Point3::new(0.0, 0.0, 0.0);
+    let vup = Vec3::new(0.0, 1.0, 0.0);
+
+    let camera = Camera::new(lookfrom, lookat, vup, 40.0, ASPECT_RATIO, 0.0, 30.0);
+
+    // Scene, every tree is an instance of the same prototype
+    let tree = tree_prototype();
+    let autumn = Arc::new(Lambertian::new(Color::new(0.7, 0.35, 0.1)));
+    let mut sampler = IndependentSampler::new(SCENE_SEED);
+    let mut instances = Vec::new();
+
+    for i in -FOREST_SIZE..FOREST_SIZE {
+        for j in -FOREST_SIZE..FOREST_SIZE {
+            let scale = 0.6 + 0.8 * sampler.next_1d();
+            let offset = Vec3::new(
+                i as f32 * 2.0 + sampler.next_1d(),
+                0.0,
+                j as f32 * 2.0 + sampler.next_1d(),
+            );
+            let transform = Transform::scaling(Vec3::new(scale, scale, scale))
+                .then(&Transform::rotation_y(360.0 * sampler.next_1d()))
+                .then(&Transform::translation(offset));
+
+            let mut instance = Instance::new(tree.clone(), transform);
+            if sampler.next_1d() < 0.2 {
+                instance.set_material(autumn.clone());
+            }
+            instances.push(instance);
+        }
+    }
+
+    let forest = InstanceTree::new(instances, 0.0, 1.0);
+    println!("{} trees", forest.len());
+
+    let mut scene = HitList::new();
+    scene.add(Arc::new(forest));
+    scene.add(Arc::new(Sphere {
+        center: Point3::new(0.0, -1000.0, 0.0),
+        radius: 1000.0,
+        material: Arc::new(Lambertian::new(Color::new(0.4, 0.5, 0.3))),
+    }));
+
+    let mut raytracer = Raytracer::new(WIDTH, HEIGHT, camera, SAMPLE_SIZE);
+    raytracer.set_sampler(SamplerKind::Sobol);
+    raytracer.set_background(Arc::new(SkyBackground::new(35.0, 120.0)));
+
+    raytracer
+        .render(&scene, &filename)
+        .expect("Could not save image");
+
+    println!("Finished in {} ms", now.elapsed().as_millis());
+}
+
+// A trunk of stacked spheres under a crown of foliage
+fn tree_prototype() -> Arc<dyn Hittable> {
+    let bark = Arc::new(Lambertian::new(Color::new(0.3, 0.2, 0.1)));
+    let leaves = Arc::new(Lambertian::new(Color::new(0.15, 0.45, 0.15)));
+    let mut objects = HitList::new();
+
+    for i in 0..6 {
+        objects.add(Arc::new(Sphere {
+            center: Point3::new(0.0, i as f32 * 0.2, 0.0),
+            radius: 0.12,
+            material: bark.clone(),
+        }));
+    }
+    for (center, radius) in [
+        (Point3::new(0.0, 1.5, 0.0), 0.6),
+        (Point3::new(0.3, 1.2, 0.2), 0.4),
+        (Point3::new(-0.3, 1.3, -0.1), 0.45),
+        (Point3::new(0.0, 2.0, 0.0), 0.35),
+    ] {
+        objects.add(Arc::new(Sphere {
+            center,
+            radius,
+            material: leaves.clone(),
+        }));
+    }
+
+    prototype(objects, 0.0, 1.0)
+}
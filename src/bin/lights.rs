@@ -0,0 +1,86 @@
+#![warn(clippy::all)]
+use raytracer::{
+    backgrounds::solid::SolidBackground,
+    bvh::BVHNode,
+    camera::Camera,
+    hit::HitList,
+    materials::{diffuse_light::DiffuseLight, lambertian::Lambertian, metal::Metal},
+    objects::sphere::Sphere,
+    rays::Color,
+    sampling::SamplerKind,
+    vectors::{Point3, Vec3},
+    Raytracer,
+};
+use std::{
+    sync::Arc,
+    time::{Instant, SystemTime},
+};
+
+const FILENAME: &str = "lights";
+const ASPECT_RATIO: f32 = 16.0 / 9.0;
+const SAMPLE_SIZE: u32 = 400;
+
+fn main() {
+    let now = Instant::now();
+
+    let filename = format!(
+        "output/{}_{}.png",
+        FILENAME,
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    );
+
+    // Dimensions
+    const WIDTH: u32 = 800;
+    const HEIGHT: u32 = (WIDTH as f32 / ASPECT_RATIO) as u32;
+
+    // Camera
+    let lookfrom = Point3::new(26.0, 3.0, 6.0);
+    let lookat = Point3::new(0.0, 2.0, 0.0);
+    let vup = Vec3::new(0.0, 1.0, 0.0);
+
+    let camera = Camera::new(lookfrom, lookat, vup, 20.0, ASPECT_RATIO, 0.0, 10.0);
+
+    // Scene, lit only by the emissive spheres
+    let mut list = HitList::new();
+
+    list.add(Arc::new(Sphere {
+        center: Point3::new(0.0, -1000.0, 0.0),
+        radius: 1000.0,
+        material: Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+    }));
+    list.add(Arc::new(Sphere {
+        center: Point3::new(0.0, 2.0, 0.0),
+        radius: 2.0,
+        material: Arc::new(Lambertian::new(Color::new(0.2, 0.4, 0.8))),
+    }));
+    list.add(Arc::new(Sphere {
+        center: Point3::new(0.0, 1.0, -4.5),
+        radius: 1.0,
+        material: Arc::new(Metal::new(Color::new(0.8, 0.8, 0.8), 0.05)),
+    }));
+    list.add(Arc::new(Sphere {
+        center: Point3::new(0.0, 7.0, 0.0),
+        radius: 1.5,
+        material: Arc::new(DiffuseLight::new(Color::new(1.0, 0.9, 0.8), 4.0)),
+    }));
+    list.add(Arc::new(Sphere {
+        center: Point3::new(3.0, 0.5, 3.0),
+        radius: 0.5,
+        material: Arc::new(DiffuseLight::new(Color::new(1.0, 0.3, 0.1), 6.0)),
+    }));
+
+    let scene = BVHNode::new(&list, 0.0, 1.0);
+
+    let mut raytracer = Raytracer::new(WIDTH, HEIGHT, camera, SAMPLE_SIZE);
+    raytracer.set_sampler(SamplerKind::Sobol);
+    raytracer.set_background(Arc::new(SolidBackground::black()));
+
+    raytracer
+        .render(&scene, &filename)
+        .expect("Could not save image");
+
+    println!("Finished in {} ms", now.elapsed().as_millis());
+}
@@ -0,0 +1,117 @@
+use raytracer::{
+    backgrounds::sky::SkyBackground,
+    bvh::BVHNode,
+    camera::Camera,
+    hit::HitList,
+    loaders::{
+        obj::load_obj,
+        ply::load_ply,
+        stl::{load_stl, StlOptions},
+    },
+    materials::lambertian::Lambertian,
+    objects::mesh::TriangleMesh,
+    rays::Color,
+    sampling::SamplerKind,
+    textures::vertex_color::VertexColor,
+    vectors::Vec3,
+    Raytracer,
+};
+use std::{
+    env,
+    error::Error,
+    path::Path,
+    process,
+    sync::Arc,
+    time::{Instant, SystemTime},
+};
+
+const FILENAME: &str = "model";
+const ASPECT_RATIO: f32 = 16.0 / 9.0;
+const SAMPLE_SIZE: u32 = 64;
+
+fn main() {
+    let now = Instant::now();
+
+    let model_path = match env::args().nth(1) {
+        Some(path) => path,
+        None => {
+            eprintln!("Usage: model <model file>");
+            process::exit(1);
+        }
+    };
+
+    let filename = format!(
+        "output/{}_{}.png",
+        FILENAME,
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    );
+
+    // Dimensions
+    const WIDTH: u32 = 960;
+    const HEIGHT: u32 = (WIDTH as f32 / ASPECT_RATIO) as u32;
+
+    // Scene
+    let list = load_model(Path::new(&model_path)).unwrap_or_else(|err| {
+        eprintln!("Could not load {}: {}", model_path, err);
+        process::exit(1);
+    });
+    let scene = BVHNode::new(&list, 0.0, 1.0);
+    let b_box = list.bounded_b_box(0.0, 1.0).unwrap();
+
+    // Camera, framing the whole model
+    let center = b_box.centroid();
+    let radius = 0.5 * (b_box.max() - b_box.min()).norm();
+    let vertical_fov: f32 = 30.0;
+    let distance = radius / (vertical_fov.to_radians() / 2.0).sin();
+
+    let lookfrom = center + distance * Vec3::new(0.6, 0.4, 1.0).unit_vector();
+    let camera = Camera::new(
+        lookfrom,
+        center,
+        Vec3::new(0.0, 1.0, 0.0),
+        vertical_fov,
+        ASPECT_RATIO,
+        0.0,
+        distance,
+    );
+
+    let mut raytracer = Raytracer::new(WIDTH, HEIGHT, camera, SAMPLE_SIZE);
+    raytracer.set_sampler(SamplerKind::Sobol);
+    raytracer.set_background(Arc::new(SkyBackground::new(45.0, 30.0)));
+
+    raytracer
+        .render(&scene, &filename)
+        .expect("Could not save image");
+
+    println!("Finished in {} ms", now.elapsed().as_millis());
+}
+
+fn load_model(path: &Path) -> Result<HitList, Box<dyn Error>> {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_ascii_lowercase());
+
+    match extension.as_deref() {
+        Some("obj") => Ok(load_obj(&path)?),
+        Some("ply") => {
+            // Vertex colors, or plain grey for meshes without them
+            let data = Arc::new(load_ply(&path)?);
+            let texture = VertexColor::new(data.clone(), Color::new(0.8, 0.8, 0.8));
+            let material = Arc::new(Lambertian::with_texture(Arc::new(texture)));
+            let mut list = HitList::new();
+            list.add(Arc::new(TriangleMesh::from_shared(data, material)));
+            Ok(list)
+        }
+        Some("stl") => {
+            let material = Arc::new(Lambertian::new(Color::new(0.7, 0.7, 0.75)));
+            let mut list = HitList::new();
+            list.add(Arc::new(load_stl(&path, material, StlOptions::default())?));
+            Ok(list)
+        }
+        _ => Err("unsupported model format".into()),
+    }
+}
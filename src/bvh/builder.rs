@@ -0,0 +1,379 @@
+use super::BVHNode;
+use crate::{
+    aabb::AAAB,
+    hit::{HitList, Hittable},
+    vectors::Point3,
+};
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+// Relative costs of visiting a node and intersecting a primitive
+pub(crate) const TRAVERSAL_COST: f32 = 0.125;
+pub(crate) const INTERSECTION_COST: f32 = 1.0;
+
+const DEFAULT_BIN_COUNT: usize = 16;
+const DEFAULT_MAX_LEAF_SIZE: usize = 4;
+// Smaller nodes are not worth the cost of spawning threads
+const PARALLEL_THRESHOLD: usize = 4096;
+
+struct Primitive {
+    object: Arc<dyn Hittable>,
+    b_box: AAAB,
+    centroid: Point3<f32>,
+}
+
+/// Builds a `BVHNode` with the surface area heuristic. Primitives are binned
+/// by their box centroids along every axis, and the cheapest split of the best
+/// axis is used until keeping a leaf costs less than splitting it.
+///
+/// Large nodes are binned on several threads and their children are built
+/// concurrently. The result does not depend on the thread count.
+#[derive(Clone)]
+pub struct BVHBuilder {
+    time0: f32,
+    time1: f32,
+    bin_count: usize,
+    max_leaf_size: usize,
+    thread_count: usize,
+}
+
+impl BVHBuilder {
+    pub fn new(time0: f32, time1: f32) -> Self {
+        Self {
+            time0,
+            time1,
+            bin_count: DEFAULT_BIN_COUNT,
+            max_leaf_size: DEFAULT_MAX_LEAF_SIZE,
+            thread_count: num_cpus::get(),
+        }
+    }
+
+    /// Time range the boxes of moving objects are computed for.
+    pub fn set_times(&mut self, time0: f32, time1: f32) {
+        self.time0 = time0;
+        self.time1 = time1;
+    }
+
+    /// More bins find better splits but take longer to evaluate.
+    pub fn set_bin_count(&mut self, bin_count: usize) {
+        self.bin_count = bin_count.max(2);
+    }
+
+    /// Leaves never hold more primitives than this, even where the heuristic
+    /// would rather not split.
+    pub fn set_max_leaf_size(&mut self, max_leaf_size: usize) {
+        self.max_leaf_size = max_leaf_size.max(1);
+    }
+
+    /// Defaults to the number of CPUs, 1 builds on the calling thread only.
+    pub fn set_thread_count(&mut self, thread_count: usize) {
+        self.thread_count = thread_count.max(1);
+    }
+
+    pub fn build(&self, list: &HitList) -> BVHNode {
+        self.build_with_stats(list).0
+    }
+
+    pub fn build_with_stats(&self, list: &HitList) -> (BVHNode, BuildStats) {
+        assert!(!list.is_empty(), "Cannot build a BVH without objects");
+
+        let now = Instant::now();
+        let objects: Vec<&Arc<dyn Hittable>> = list.iter().collect();
+        let b_boxes = map_chunks(&objects, self.thread_count, |chunk| {
+            chunk
+                .iter()
+                .map(|object| object.get_b_box(self.time0, self.time1))
+                .collect::<Vec<_>>()
+        });
+
+        let mut primitives = Vec::with_capacity(objects.len());
+        let mut unbounded = Vec::new();
+        for (object, b_box) in objects.into_iter().zip(b_boxes.into_iter().flatten()) {
+            match b_box {
+                Some(b_box) => primitives.push(Primitive {
+                    object: Arc::clone(object),
+                    b_box,
+                    centroid: b_box.centroid(),
+                }),
+                None => unbounded.push(Arc::clone(object)),
+            }
+        }
+        let bounds_time = now.elapsed();
+
+        let now = Instant::now();
+        let parallel_nodes = AtomicUsize::new(0);
+        let mut root = if primitives.is_empty() {
+            // Only unbounded objects, an empty leaf is never hit
+            let origin = Point3::default();
+            BVHNode::leaf(AAAB::new(origin, origin), Vec::new())
+        } else {
+            self.build_node(&mut primitives, self.thread_count, &parallel_nodes)
+        };
+        root.set_unbounded(unbounded);
+
+        let stats = BuildStats {
+            thread_count: self.thread_count,
+            parallel_nodes: parallel_nodes.into_inner(),
+            bounds_time,
+            hierarchy_time: now.elapsed(),
+        };
+
+        (root, stats)
+    }
+
+    fn build_node(
+        &self,
+        primitives: &mut [Primitive],
+        threads: usize,
+        parallel_nodes: &AtomicUsize,
+    ) -> BVHNode {
+        let (b_box, centroid_box) = map_chunks(primitives, threads, bounds)
+            .into_iter()
+            .reduce(|(a, a_centroids), (b, b_centroids)| {
+                (
+                    AAAB::new_surrounding_box(a, b),
+                    AAAB::new_surrounding_box(a_centroids, b_centroids),
+                )
+            })
+            .unwrap();
+
+        let (middle, axis) = match self.split(primitives, &b_box, &centroid_box, threads) {
+            Some(split) => split,
+            None => {
+                return BVHNode::leaf(
+                    b_box,
+                    primitives
+                        .iter()
+                        .map(|primitive| primitive.object.clone())
+                        .collect(),
+                )
+            }
+        };
+
+        let count = primitives.len();
+        let (left, right) = primitives.split_at_mut(middle);
+        if threads == 1 || count < PARALLEL_THRESHOLD {
+            let left = self.build_node(left, 1, parallel_nodes);
+            let right = self.build_node(right, 1, parallel_nodes);
+            return BVHNode::interior(left, right, axis);
+        }
+
+        // Threads are shared out by the size of each side
+        parallel_nodes.fetch_add(1, Ordering::Relaxed);
+        let left_threads = (threads * middle / count).clamp(1, threads - 1);
+        let (left, right) = thread::scope(|s| {
+            let left = s.spawn(|| self.build_node(left, left_threads, parallel_nodes));
+            let right = self.build_node(right, threads - left_threads, parallel_nodes);
+            (left.join().unwrap(), right)
+        });
+
+        BVHNode::interior(left, right, axis)
+    }
+
+    // Partitions the primitives for the cheapest split and returns where the
+    // right half starts and the split axis, or `None` if they should stay
+    // together in a leaf
+    fn split(
+        &self,
+        primitives: &mut [Primitive],
+        b_box: &AAAB,
+        centroid_box: &AAAB,
+        threads: usize,
+    ) -> Option<(usize, u32)> {
+        let count = primitives.len();
+        if count == 1 {
+            return None;
+        }
+
+        let area = b_box.surface_area();
+
+        // Best split as (cost, axis, first bin on the right)
+        let mut best: Option<(f32, u32, usize)> = None;
+        for axis in 0..3 {
+            let bins = match self.fill_bins(primitives, centroid_box, axis, threads) {
+                Some(bins) => bins,
+                None => continue,
+            };
+            // Boxes and counts of everything right of each bin boundary
+            let mut right = vec![(None, 0); self.bin_count];
+            let mut accumulated: (Option<AAAB>, usize) = (None, 0);
+            for bin in (1..self.bin_count).rev() {
+                accumulated = merge(accumulated, bins[bin]);
+                right[bin] = accumulated;
+            }
+
+            let mut left: (Option<AAAB>, usize) = (None, 0);
+            for bin in 1..self.bin_count {
+                left = merge(left, bins[bin - 1]);
+                if let ((Some(left_box), left_count), (Some(right_box), right_count)) =
+                    (left, right[bin])
+                {
+                    let cost = TRAVERSAL_COST
+                        + INTERSECTION_COST
+                            * (left_box.surface_area() * left_count as f32
+                                + right_box.surface_area() * right_count as f32)
+                            / area;
+                    if best.is_none_or(|(best_cost, _, _)| cost < best_cost) {
+                        best = Some((cost, axis, bin));
+                    }
+                }
+            }
+        }
+
+        match best {
+            Some((cost, axis, bin))
+                if cost < INTERSECTION_COST * count as f32 || count > self.max_leaf_size =>
+            {
+                let middle = partition(primitives, |primitive| {
+                    self.bin_index(&primitive.centroid, centroid_box, axis) < bin
+                });
+                Some((middle, axis))
+            }
+            Some(_) => None,
+            // All centroids coincide, only the leaf size limit forces a split
+            None if count > self.max_leaf_size => Some((count / 2, 0)),
+            None => None,
+        }
+    }
+
+    fn fill_bins(
+        &self,
+        primitives: &[Primitive],
+        centroid_box: &AAAB,
+        axis: u32,
+        threads: usize,
+    ) -> Option<Vec<(Option<AAAB>, usize)>> {
+        let extent = centroid_box.max().get(axis)? - centroid_box.min().get(axis)?;
+        if extent <= 0.0 {
+            return None;
+        }
+
+        map_chunks(primitives, threads, |chunk| {
+            let mut bins = vec![(None, 0); self.bin_count];
+            for primitive in chunk {
+                let bin = self.bin_index(&primitive.centroid, centroid_box, axis);
+                bins[bin] = merge(bins[bin], (Some(primitive.b_box), 1));
+            }
+
+            bins
+        })
+        .into_iter()
+        .reduce(|mut bins, chunk_bins| {
+            for (bin, chunk_bin) in bins.iter_mut().zip(chunk_bins) {
+                *bin = merge(*bin, chunk_bin);
+            }
+
+            bins
+        })
+    }
+
+    fn bin_index(&self, centroid: &Point3<f32>, centroid_box: &AAAB, axis: u32) -> usize {
+        let min = centroid_box.min().get(axis).unwrap();
+        let extent = centroid_box.max().get(axis).unwrap() - min;
+        let bin = ((centroid.get(axis).unwrap() - min) / extent * self.bin_count as f32) as usize;
+
+        bin.min(self.bin_count - 1)
+    }
+}
+
+// Box of the primitives and box of their centroids
+fn bounds(primitives: &[Primitive]) -> (AAAB, AAAB) {
+    let first = (
+        primitives[0].b_box,
+        AAAB::new(primitives[0].centroid, primitives[0].centroid),
+    );
+
+    primitives[1..]
+        .iter()
+        .fold(first, |(b_box, centroid_box), primitive| {
+            (
+                AAAB::new_surrounding_box(b_box, primitive.b_box),
+                AAAB::new_surrounding_box(
+                    centroid_box,
+                    AAAB::new(primitive.centroid, primitive.centroid),
+                ),
+            )
+        })
+}
+
+// Runs `f` over consecutive chunks of `items` on up to `threads` threads and
+// returns the results in order. Small inputs stay on the calling thread.
+fn map_chunks<T, R, F>(items: &[T], threads: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&[T]) -> R + Sync,
+{
+    if threads == 1 || items.len() < PARALLEL_THRESHOLD {
+        return vec![f(items)];
+    }
+
+    let f = &f;
+    thread::scope(|s| {
+        let handles: Vec<_> = items
+            .chunks(items.len().div_ceil(threads))
+            .map(|chunk| s.spawn(move || f(chunk)))
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect()
+    })
+}
+
+fn merge(a: (Option<AAAB>, usize), b: (Option<AAAB>, usize)) -> (Option<AAAB>, usize) {
+    let b_box = match (a.0, b.0) {
+        (Some(a), Some(b)) => Some(AAAB::new_surrounding_box(a, b)),
+        (a, b) => a.or(b),
+    };
+
+    (b_box, a.1 + b.1)
+}
+
+// Moves the primitives matching `is_left` to the front, returning how many
+// there are
+fn partition<F>(primitives: &mut [Primitive], is_left: F) -> usize
+where
+    F: Fn(&Primitive) -> bool,
+{
+    let mut middle = 0;
+    for i in 0..primitives.len() {
+        if is_left(&primitives[i]) {
+            primitives.swap(i, middle);
+            middle += 1;
+        }
+    }
+
+    middle
+}
+
+/// Timings of a build, see `BVHNode::report` for the shape of the result.
+#[derive(Clone, Copy, Debug)]
+pub struct BuildStats {
+    pub thread_count: usize,
+    /// Nodes whose two children were built on separate threads
+    pub parallel_nodes: usize,
+    pub bounds_time: Duration,
+    pub hierarchy_time: Duration,
+}
+
+impl fmt::Display for BuildStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} threads, {} parallel nodes, bounds in {} ms, hierarchy in {} ms",
+            self.thread_count,
+            self.parallel_nodes,
+            self.bounds_time.as_millis(),
+            self.hierarchy_time.as_millis()
+        )
+    }
+}
@@ -0,0 +1,65 @@
+use crate::{
+    hit::{HitList, Hittable},
+    materials::lambertian::Lambertian,
+    objects::{moving_sphere::MovingSphere, sphere::Sphere},
+    rays::{Color, Ray},
+    sampling::{independent::IndependentSampler, Sampler},
+    vectors::Vec3,
+};
+use std::sync::Arc;
+
+// Spheres scattered over [-10, 10]^3, the same ones for a given `count`
+pub fn random_spheres(count: usize) -> HitList {
+    let mut sampler = IndependentSampler::new(3);
+    let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    let mut list = HitList::new();
+    for _ in 0..count {
+        list.add(Arc::new(Sphere {
+            center: Vec3::new_random(&mut sampler, -10.0, 10.0),
+            radius: 0.1 + 0.4 * sampler.next_1d(),
+            material: material.clone(),
+        }));
+    }
+
+    list
+}
+
+// Spheres drifting by up to `drift` between time 0 and 1
+pub fn moving_spheres(sampler: &mut dyn Sampler, count: usize, drift: f32) -> HitList {
+    let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    let mut list = HitList::new();
+    for _ in 0..count {
+        let center = Vec3::new_random(sampler, -10.0, 10.0);
+        list.add(Arc::new(MovingSphere {
+            center_start: center,
+            center_end: center + Vec3::new_random(sampler, -drift, drift),
+            time_start: 0.0,
+            time_end: 1.0,
+            radius: 0.3,
+            material: material.clone(),
+        }));
+    }
+
+    list
+}
+
+// Compares closest hits against a plain list, for random rays through the
+// middle of the spheres above
+pub fn assert_hits_match(
+    scene: &dyn Hittable,
+    list: &HitList,
+    sampler: &mut dyn Sampler,
+    time: f32,
+) {
+    for _ in 0..300 {
+        let origin = Vec3::new_random(sampler, -15.0, 15.0);
+        let target = Vec3::new_random(sampler, -5.0, 5.0);
+        let ray = Ray::new(origin, target - origin, time);
+
+        let expected = list.hit(&ray, 0.001, f32::INFINITY).map(|hit| hit.t);
+        assert_eq!(
+            scene.hit(&ray, 0.001, f32::INFINITY).map(|hit| hit.t),
+            expected
+        );
+    }
+}
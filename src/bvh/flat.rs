@@ -0,0 +1,516 @@
+use super::{
+    builder::{INTERSECTION_COST, TRAVERSAL_COST},
+    hit_closest, BVHBuilder, BVHContent, BVHNode, BuildReport,
+};
+use crate::{
+    aabb::AAAB,
+    hit::{Hit, HitList, Hittable},
+    rays::Ray,
+    vectors::Vec3,
+};
+use std::{collections::HashMap, sync::Arc};
+
+// Hierarchies up to this deep are traversed without allocating a stack
+const INLINE_STACK_DEPTH: usize = 64;
+const DEFAULT_REBUILD_THRESHOLD: f32 = 1.5;
+
+// Leaves have `count > 0` and their primitives start at `offset`. Interior
+// nodes are followed by their first child and `offset` is the second one.
+struct FlatNode {
+    b_box: AAAB,
+    offset: u32,
+    count: u32,
+    axis: u8,
+}
+
+/// `BVHNode` laid out depth first in a single `Vec`, with the leaves indexing
+/// ranges of one primitive array. Traversal is iterative and visits the child
+/// nearest to the ray first, so far subtrees are often culled by a closer hit.
+///
+/// For animation, objects that moved are marked with `mark_changed` and
+/// `refit` updates the boxes above them for the next frame's time range,
+/// keeping the tree structure.
+pub struct FlatBVH {
+    nodes: Vec<FlatNode>,
+    primitives: Vec<Arc<dyn Hittable>>,
+    unbounded: Vec<Arc<dyn Hittable>>,
+    report: BuildReport,
+    // Leaves holding each bounded object, keyed by its address. The same
+    // object can be added more than once.
+    leaves: HashMap<usize, Vec<u32>>,
+    // Nodes whose box is out of date
+    changed: Vec<bool>,
+    rebuild_threshold: f32,
+    // Settings reused when `refit` rebuilds
+    builder: BVHBuilder,
+}
+
+/// What `FlatBVH::refit` did.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RefitResult {
+    /// Nothing was marked as changed
+    Unchanged,
+    /// Boxes were updated, `sah_ratio` is the SAH cost relative to the last
+    /// full build
+    Refitted { sah_ratio: f32 },
+    /// The refitted tree was too costly, its cost could not be measured, or
+    /// an object lost its bounding box, so it was built again from scratch
+    Rebuilt,
+}
+
+impl FlatBVH {
+    /// Builds the hierarchy with the default `BVHBuilder` settings.
+    pub fn new(list: &HitList, time0: f32, time1: f32) -> Self {
+        let builder = BVHBuilder::new(time0, time1);
+
+        Self::from_tree(&builder.build(list), &builder)
+    }
+
+    /// Flattens `tree`, which `builder` built. Rebuilds use the same builder
+    /// settings with the new time range.
+    pub fn from_tree(tree: &BVHNode, builder: &BVHBuilder) -> Self {
+        let report = tree.report();
+        let mut flat = Self {
+            nodes: Vec::with_capacity(report.node_count),
+            primitives: Vec::with_capacity(report.primitive_count),
+            unbounded: tree.unbounded.clone(),
+            report,
+            leaves: HashMap::with_capacity(report.primitive_count),
+            changed: vec![false; report.node_count],
+            rebuild_threshold: DEFAULT_REBUILD_THRESHOLD,
+            builder: builder.clone(),
+        };
+        // A tree of unbounded objects only has no nodes worth keeping
+        if report.primitive_count > 0 {
+            flat.flatten(tree);
+        }
+
+        flat
+    }
+
+    fn flatten(&mut self, node: &BVHNode) {
+        let node_index = self.nodes.len();
+        self.nodes.push(FlatNode {
+            b_box: node.b_box,
+            offset: self.primitives.len() as u32,
+            count: 0,
+            axis: 0,
+        });
+
+        match &node.content {
+            BVHContent::Interior(left, right, axis) => {
+                self.flatten(left);
+                let second_child = self.nodes.len() as u32;
+                self.flatten(right);
+
+                self.nodes[node_index].offset = second_child;
+                self.nodes[node_index].axis = *axis as u8;
+            }
+            BVHContent::Leaf(objects) => {
+                self.nodes[node_index].count = objects.len() as u32;
+                for object in objects {
+                    self.leaves
+                        .entry(address(object))
+                        .or_default()
+                        .push(node_index as u32);
+                    self.primitives.push(object.clone());
+                }
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.primitives.len() + self.unbounded.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Statistics of the tree this was flattened from, or of the last
+    /// rebuild. Refitting does not update them.
+    pub fn report(&self) -> BuildReport {
+        self.report
+    }
+
+    /// Expected cost of a ray under the surface area heuristic with the
+    /// current boxes, comparable to `report().sah_cost`.
+    pub fn sah_cost(&self) -> f32 {
+        let root_area = match self.nodes.first() {
+            Some(root) => root.b_box.surface_area(),
+            None => return 0.0,
+        };
+
+        self.nodes
+            .iter()
+            .map(|node| {
+                let area_ratio = node.b_box.surface_area() / root_area;
+                if node.count > 0 {
+                    INTERSECTION_COST * area_ratio * node.count as f32
+                } else {
+                    TRAVERSAL_COST * area_ratio
+                }
+            })
+            .sum()
+    }
+
+    /// `refit` rebuilds the whole tree once its SAH cost grows past this
+    /// multiple of the cost after the last build. Defaults to 1.5.
+    pub fn set_rebuild_threshold(&mut self, rebuild_threshold: f32) {
+        self.rebuild_threshold = rebuild_threshold.max(1.0);
+    }
+
+    /// Flags `object` for the next `refit`. Returns `false` if it is not in
+    /// the hierarchy, unbounded objects included.
+    pub fn mark_changed(&mut self, object: &Arc<dyn Hittable>) -> bool {
+        match self.leaves.get(&address(object)) {
+            Some(leaves) => {
+                for &leaf in leaves {
+                    self.changed[leaf as usize] = true;
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn mark_all_changed(&mut self) {
+        for (node, changed) in self.nodes.iter().zip(self.changed.iter_mut()) {
+            *changed = node.count > 0;
+        }
+    }
+
+    /// Recomputes the boxes of the changed leaves for `[time0, time1]` and
+    /// of every node above them, bottom up.
+    pub fn refit(&mut self, time0: f32, time1: f32) -> RefitResult {
+        if !self.changed.contains(&true) {
+            return RefitResult::Unchanged;
+        }
+
+        // Children always come after their parent
+        for index in (0..self.nodes.len()).rev() {
+            let node = &self.nodes[index];
+            let b_box = if node.count > 0 {
+                if !self.changed[index] {
+                    continue;
+                }
+
+                let start = node.offset as usize;
+                let b_box = self.primitives[start..start + node.count as usize]
+                    .iter()
+                    .map(|object| object.get_b_box(time0, time1))
+                    .reduce(|a, b| Some(AAAB::new_surrounding_box(a?, b?)))
+                    .flatten();
+                match b_box {
+                    Some(b_box) => b_box,
+                    None => {
+                        self.rebuild(time0, time1);
+                        return RefitResult::Rebuilt;
+                    }
+                }
+            } else {
+                let (first, second) = (index + 1, node.offset as usize);
+                if !self.changed[first] && !self.changed[second] {
+                    continue;
+                }
+
+                AAAB::new_surrounding_box(self.nodes[first].b_box, self.nodes[second].b_box)
+            };
+
+            self.nodes[index].b_box = b_box;
+            self.changed[index] = true;
+        }
+        self.changed.fill(false);
+
+        // Boxes collapsed to points have no area, so the ratio means nothing
+        let sah_ratio = self.sah_cost() / self.report.sah_cost;
+        if !sah_ratio.is_finite() || sah_ratio > self.rebuild_threshold {
+            self.rebuild(time0, time1);
+            return RefitResult::Rebuilt;
+        }
+
+        RefitResult::Refitted { sah_ratio }
+    }
+
+    fn rebuild(&mut self, time0: f32, time1: f32) {
+        let mut list = HitList::new();
+        for object in self.primitives.iter().chain(self.unbounded.iter()) {
+            list.add(object.clone());
+        }
+
+        let mut builder = self.builder.clone();
+        builder.set_times(time0, time1);
+        let rebuild_threshold = self.rebuild_threshold;
+        *self = Self::from_tree(&builder.build(&list), &builder);
+        self.rebuild_threshold = rebuild_threshold;
+    }
+
+    // Closest hit among the bounded objects, or `closest_hit` if none is
+    // nearer. `stack` must hold `report.depth` entries.
+    fn traverse(
+        &self,
+        stack: &mut [u32],
+        ray: &Ray,
+        t_min: f32,
+        t_max: f32,
+        mut closest_hit: Option<Hit>,
+    ) -> Option<Hit> {
+        let origin = ray.origin();
+        let direction = ray.direction();
+        let inverse_direction = Vec3::new(
+            1.0 / direction.x(),
+            1.0 / direction.y(),
+            1.0 / direction.z(),
+        );
+        let dir_is_neg = [
+            direction.x() < 0.0,
+            direction.y() < 0.0,
+            direction.z() < 0.0,
+        ];
+
+        stack[0] = 0;
+        let mut stack_size = 1;
+        while stack_size > 0 {
+            stack_size -= 1;
+            let node_index = stack[stack_size];
+            let node = &self.nodes[node_index as usize];
+            let t_max = closest_hit.as_ref().map_or(t_max, |hit| hit.t);
+            if node
+                .b_box
+                .range_with_inverse(&origin, &inverse_direction, t_min, t_max)
+                .is_none()
+            {
+                continue;
+            }
+
+            if node.count > 0 {
+                let start = node.offset as usize;
+                for object in self.primitives[start..start + node.count as usize].iter() {
+                    let t_max = closest_hit.as_ref().map_or(t_max, |hit| hit.t);
+                    if let Some(hit) = object.hit(ray, t_min, t_max) {
+                        closest_hit = Some(hit);
+                    }
+                }
+            } else {
+                // Pushed last, so the near child is popped first
+                let (near, far) = if dir_is_neg[node.axis as usize] {
+                    (node.offset, node_index + 1)
+                } else {
+                    (node_index + 1, node.offset)
+                };
+                stack[stack_size] = far;
+                stack[stack_size + 1] = near;
+                stack_size += 2;
+            }
+        }
+
+        closest_hit
+    }
+}
+
+fn address(object: &Arc<dyn Hittable>) -> usize {
+    Arc::as_ptr(object) as *const () as usize
+}
+
+impl Hittable for FlatBVH {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit> {
+        let closest_hit = hit_closest(&self.unbounded, ray, t_min, t_max);
+        if self.nodes.is_empty() {
+            return closest_hit;
+        }
+
+        // Every level holds at most one pending far child
+        if self.report.depth <= INLINE_STACK_DEPTH {
+            self.traverse(&mut [0; INLINE_STACK_DEPTH], ray, t_min, t_max, closest_hit)
+        } else {
+            self.traverse(
+                &mut vec![0; self.report.depth],
+                ray,
+                t_min,
+                t_max,
+                closest_hit,
+            )
+        }
+    }
+
+    fn get_b_box(&self, _time0: f32, _time1: f32) -> Option<AAAB> {
+        if self.unbounded.is_empty() {
+            self.nodes.first().map(|node| node.b_box)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bvh::fixtures::{assert_hits_match, moving_spheres, random_spheres},
+        materials::lambertian::Lambertian,
+        objects::sphere::Sphere,
+        rays::Color,
+        sampling::independent::IndependentSampler,
+    };
+
+    #[test]
+    fn test_hits_match_tree() {
+        let list = random_spheres(800);
+        let mut sampler = IndependentSampler::new(5);
+        let tree = BVHNode::new(&list, 0.0, 1.0);
+        let flat = FlatBVH::from_tree(&tree, &BVHBuilder::new(0.0, 1.0));
+        assert_eq!(flat.len(), 800);
+        assert_eq!(flat.nodes.len(), tree.report().node_count);
+
+        assert_hits_match(&tree, &list, &mut sampler, 0.0);
+        assert_hits_match(&flat, &list, &mut sampler, 0.0);
+    }
+
+    #[test]
+    fn test_axis_aligned_rays() {
+        // Zero direction components give infinite reciprocals in the slab test
+        let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let mut list = HitList::new();
+        for i in 0..20 {
+            list.add(Arc::new(Sphere {
+                center: Vec3::new(i as f32 * 3.0, 0.0, 0.0),
+                radius: 1.0,
+                material: material.clone(),
+            }));
+        }
+        let flat = FlatBVH::new(&list, 0.0, 1.0);
+
+        let forward = Ray::new(Vec3::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), 0.0);
+        let backward = Ray::new(Vec3::new(100.0, 0.0, 0.0), Vec3::new(-1.0, 0.0, 0.0), 0.0);
+        let down = Ray::new(Vec3::new(30.0, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0), 0.0);
+        let miss = Ray::new(Vec3::new(1.5, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0), 0.0);
+
+        assert_eq!(flat.hit(&forward, 0.001, f32::INFINITY).unwrap().t, 4.0);
+        assert_eq!(flat.hit(&backward, 0.001, f32::INFINITY).unwrap().t, 42.0);
+        assert_eq!(flat.hit(&down, 0.001, f32::INFINITY).unwrap().t, 4.0);
+        assert!(flat.hit(&miss, 0.001, f32::INFINITY).is_none());
+    }
+
+    #[test]
+    fn test_deeper_than_inline_stack() {
+        // A chain with one sphere hanging off every level
+        let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let mut list = HitList::new();
+        for i in 0..100 {
+            list.add(Arc::new(Sphere {
+                center: Vec3::new(i as f32 * 3.0, 0.0, 0.0),
+                radius: 1.0,
+                material: material.clone(),
+            }));
+        }
+        let leaf = |object: &Arc<dyn Hittable>| {
+            BVHNode::leaf(object.get_b_box(0.0, 1.0).unwrap(), vec![object.clone()])
+        };
+        let mut tree = leaf(list.get(99).unwrap());
+        for object in list.iter().take(99).rev() {
+            tree = BVHNode::interior(leaf(object), tree, 0);
+        }
+        let flat = FlatBVH::from_tree(&tree, &BVHBuilder::new(0.0, 1.0));
+        assert_eq!(flat.report().depth, 100);
+
+        let backward = Ray::new(Vec3::new(400.0, 0.0, 0.0), Vec3::new(-1.0, 0.0, 0.0), 0.0);
+        assert_eq!(flat.hit(&backward, 0.001, f32::INFINITY).unwrap().t, 102.0);
+        let mut sampler = IndependentSampler::new(10);
+        assert_hits_match(&flat, &list, &mut sampler, 0.0);
+    }
+
+    #[test]
+    fn test_refit_changed_objects() {
+        let mut sampler = IndependentSampler::new(8);
+        let list = moving_spheres(&mut sampler, 300, 1.0);
+        let mut flat = FlatBVH::new(&list, 0.0, 0.0);
+        flat.set_rebuild_threshold(f32::INFINITY);
+        assert_eq!(flat.refit(1.0, 1.0), RefitResult::Unchanged);
+
+        // Marking a single sphere grows the boxes above it to its new place
+        let moved = list.get(0).unwrap().clone();
+        assert!(flat.mark_changed(&moved));
+        flat.refit(1.0, 1.0);
+        let b_box = moved.get_b_box(1.0, 1.0).unwrap();
+        assert!(flat.nodes[0].b_box.contains(&b_box.min()));
+        assert!(flat.nodes[0].b_box.contains(&b_box.max()));
+
+        flat.mark_all_changed();
+        match flat.refit(1.0, 1.0) {
+            RefitResult::Refitted { sah_ratio } => assert!(sah_ratio < 2.0),
+            result => panic!("Expected a refit, got {:?}", result),
+        }
+        assert_hits_match(&flat, &list, &mut sampler, 1.0);
+    }
+
+    #[test]
+    fn test_refit_duplicated_object() {
+        let mut sampler = IndependentSampler::new(11);
+        let list = moving_spheres(&mut sampler, 1, 5.0);
+        let moved = list.get(0).unwrap().clone();
+        let leaf = || BVHNode::leaf(moved.get_b_box(0.0, 0.0).unwrap(), vec![moved.clone()]);
+        let tree = BVHNode::interior(leaf(), leaf(), 0);
+        let mut flat = FlatBVH::from_tree(&tree, &BVHBuilder::new(0.0, 0.0));
+        flat.set_rebuild_threshold(f32::INFINITY);
+
+        // Both leaves follow the object
+        assert!(flat.mark_changed(&moved));
+        flat.refit(1.0, 1.0);
+        let b_box = moved.get_b_box(1.0, 1.0).unwrap();
+        for node in flat.nodes.iter() {
+            assert_eq!(node.b_box.min(), b_box.min());
+            assert_eq!(node.b_box.max(), b_box.max());
+        }
+    }
+
+    #[test]
+    fn test_rebuild_past_threshold() {
+        // Everything moves across the scene, so the old grouping is useless
+        let mut sampler = IndependentSampler::new(9);
+        let list = moving_spheres(&mut sampler, 300, 20.0);
+        let mut flat = FlatBVH::new(&list, 0.0, 0.0);
+        let cost = flat.report().sah_cost;
+
+        flat.mark_all_changed();
+        assert_eq!(flat.refit(1.0, 1.0), RefitResult::Rebuilt);
+        assert!(flat.sah_cost() < 1.5 * cost);
+        assert_hits_match(&flat, &list, &mut sampler, 1.0);
+    }
+
+    #[test]
+    fn test_rebuild_keeps_builder_settings() {
+        // Nested spheres can't be split apart, so leaves are as big as allowed
+        let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let mut list = HitList::new();
+        for i in 0..10 {
+            list.add(Arc::new(Sphere {
+                center: Vec3::new(0.0, 0.0, 0.0),
+                radius: 1.0 + i as f32,
+                material: material.clone(),
+            }));
+        }
+        let mut builder = BVHBuilder::new(0.0, 1.0);
+        builder.set_max_leaf_size(2);
+        let mut flat = FlatBVH::from_tree(&builder.build(&list), &builder);
+
+        flat.rebuild(0.0, 1.0);
+        assert_eq!(flat.report().max_leaf_size, 2);
+    }
+
+    #[test]
+    fn test_rebuild_without_surface_area() {
+        let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let mut list = HitList::new();
+        for _ in 0..4 {
+            list.add(Arc::new(Sphere {
+                center: Vec3::new(0.0, 0.0, 0.0),
+                radius: 0.0,
+                material: material.clone(),
+            }));
+        }
+        let mut flat = FlatBVH::new(&list, 0.0, 1.0);
+
+        flat.mark_all_changed();
+        assert_eq!(flat.refit(0.0, 1.0), RefitResult::Rebuilt);
+    }
+}
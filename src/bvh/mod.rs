@@ -0,0 +1,285 @@
+mod builder;
+#[cfg(test)]
+mod fixtures;
+mod flat;
+
+pub use builder::{BVHBuilder, BuildStats};
+pub use flat::{FlatBVH, RefitResult};
+
+use crate::hit::HitList;
+use crate::{aabb::AAAB, hit::Hittable};
+use crate::{hit::Hit, rays::Ray};
+use std::{fmt, sync::Arc};
+
+pub struct BVHNode {
+    b_box: AAAB,
+    content: BVHContent,
+    // Objects without a bounding box, like planes, tested on every ray. Only
+    // ever filled at the root.
+    unbounded: Vec<Arc<dyn Hittable>>,
+}
+
+enum BVHContent {
+    /// Children and the axis they were split along
+    Interior(Box<BVHNode>, Box<BVHNode>, u32),
+    Leaf(Vec<Arc<dyn Hittable>>),
+}
+
+impl BVHNode {
+    /// Builds the hierarchy with the default `BVHBuilder` settings. `list` is
+    /// left untouched, and may contain unbounded objects.
+    pub fn new(list: &HitList, time0: f32, time1: f32) -> Self {
+        BVHBuilder::new(time0, time1).build(list)
+    }
+
+    pub(crate) fn interior(left: BVHNode, right: BVHNode, axis: u32) -> Self {
+        Self {
+            b_box: AAAB::new_surrounding_box(left.b_box, right.b_box),
+            content: BVHContent::Interior(Box::new(left), Box::new(right), axis),
+            unbounded: Vec::new(),
+        }
+    }
+
+    pub(crate) fn leaf(b_box: AAAB, objects: Vec<Arc<dyn Hittable>>) -> Self {
+        Self {
+            b_box,
+            content: BVHContent::Leaf(objects),
+            unbounded: Vec::new(),
+        }
+    }
+
+    pub(crate) fn set_unbounded(&mut self, unbounded: Vec<Arc<dyn Hittable>>) {
+        self.unbounded = unbounded;
+    }
+
+    /// Statistics on the shape of the hierarchy, to compare builds.
+    pub fn report(&self) -> BuildReport {
+        let mut report = BuildReport {
+            unbounded_count: self.unbounded.len(),
+            ..BuildReport::default()
+        };
+        self.collect_report(&mut report, 1, self.b_box.surface_area());
+
+        report
+    }
+
+    fn collect_report(&self, report: &mut BuildReport, depth: usize, root_area: f32) {
+        let area_ratio = self.b_box.surface_area() / root_area;
+        report.node_count += 1;
+        report.depth = report.depth.max(depth);
+
+        match &self.content {
+            BVHContent::Interior(left, right, _) => {
+                report.sah_cost += builder::TRAVERSAL_COST * area_ratio;
+                left.collect_report(report, depth + 1, root_area);
+                right.collect_report(report, depth + 1, root_area);
+            }
+            BVHContent::Leaf(objects) => {
+                report.sah_cost += builder::INTERSECTION_COST * area_ratio * objects.len() as f32;
+                report.leaf_count += 1;
+                report.primitive_count += objects.len();
+                report.max_leaf_size = report.max_leaf_size.max(objects.len());
+            }
+        }
+    }
+
+    // Closest hit among the bounded objects
+    fn hit_tree(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit> {
+        if !self.b_box.is_in(ray, t_min, t_max) {
+            return None;
+        }
+
+        match &self.content {
+            BVHContent::Interior(left, right, _) => {
+                let left_hit = left.hit_tree(ray, t_min, t_max);
+                let t_max = match left_hit {
+                    Some(ref hit) => hit.t,
+                    None => t_max,
+                };
+                let right_hit = right.hit_tree(ray, t_min, t_max);
+
+                right_hit.or(left_hit)
+            }
+            BVHContent::Leaf(objects) => hit_closest(objects, ray, t_min, t_max),
+        }
+    }
+}
+
+impl Hittable for BVHNode {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit> {
+        let unbounded_hit = hit_closest(&self.unbounded, ray, t_min, t_max);
+        let t_max = unbounded_hit.as_ref().map_or(t_max, |hit| hit.t);
+
+        self.hit_tree(ray, t_min, t_max).or(unbounded_hit)
+    }
+
+    fn get_b_box(&self, _time0: f32, _time1: f32) -> Option<AAAB> {
+        if self.unbounded.is_empty() {
+            Some(self.b_box)
+        } else {
+            None
+        }
+    }
+}
+
+pub(crate) fn hit_closest(
+    objects: &[Arc<dyn Hittable>],
+    ray: &Ray,
+    t_min: f32,
+    t_max: f32,
+) -> Option<Hit> {
+    let mut closest_hit: Option<Hit> = None;
+    for object in objects {
+        let t_max = closest_hit.as_ref().map_or(t_max, |hit| hit.t);
+        if let Some(hit) = object.hit(ray, t_min, t_max) {
+            closest_hit = Some(hit);
+        }
+    }
+
+    closest_hit
+}
+
+/// Shape of a built hierarchy. `sah_cost` is the expected cost of a ray
+/// under the surface area heuristic, relative to intersecting one primitive.
+/// Unbounded objects are outside the hierarchy and not part of the cost.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct BuildReport {
+    pub sah_cost: f32,
+    pub depth: usize,
+    pub node_count: usize,
+    pub leaf_count: usize,
+    pub primitive_count: usize,
+    pub max_leaf_size: usize,
+    pub unbounded_count: usize,
+}
+
+impl fmt::Display for BuildReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} primitives, {} nodes, {} leaves (up to {} primitives), depth {}, SAH cost {:.2}",
+            self.primitive_count,
+            self.node_count,
+            self.leaf_count,
+            self.max_leaf_size,
+            self.depth,
+            self.sah_cost
+        )?;
+
+        if self.unbounded_count > 0 {
+            write!(f, ", {} unbounded", self.unbounded_count)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        fixtures::{assert_hits_match, random_spheres},
+        *,
+    };
+    use crate::{
+        materials::lambertian::Lambertian,
+        objects::{plane::Plane, sphere::Sphere},
+        rays::Color,
+        sampling::independent::IndependentSampler,
+        vectors::{Point3, Vec3},
+    };
+
+    #[test]
+    fn test_hits_match_list() {
+        let list = random_spheres(500);
+        let bvh = BVHNode::new(&list, 0.0, 1.0);
+        let mut sampler = IndependentSampler::new(4);
+
+        assert_hits_match(&bvh, &list, &mut sampler, 0.0);
+    }
+
+    #[test]
+    fn test_report() {
+        let list = random_spheres(1000);
+        let mut builder = BVHBuilder::new(0.0, 1.0);
+        builder.set_max_leaf_size(4);
+        let report = builder.build(&list).report();
+
+        assert_eq!(report.primitive_count, 1000);
+        assert_eq!(report.node_count, 2 * report.leaf_count - 1);
+        assert!(report.max_leaf_size <= 4);
+        assert!(report.depth < 40);
+
+        // A single leaf costs one intersection per primitive
+        let flat = BVHNode::leaf(
+            list.get_b_box(0.0, 1.0).unwrap(),
+            list.iter().cloned().collect(),
+        );
+        assert!((flat.report().sah_cost - 1000.0).abs() < 1e-3);
+        assert!(BVHNode::new(&list, 0.0, 1.0).report().sah_cost < 100.0);
+    }
+
+    #[test]
+    fn test_parallel_build_matches_serial() {
+        let list = random_spheres(20_000);
+        let mut builder = BVHBuilder::new(0.0, 1.0);
+        builder.set_thread_count(1);
+        let (serial, serial_stats) = builder.build_with_stats(&list);
+        builder.set_thread_count(4);
+        let (parallel, parallel_stats) = builder.build_with_stats(&list);
+
+        assert_eq!(serial_stats.parallel_nodes, 0);
+        assert!(parallel_stats.parallel_nodes > 0);
+        assert_eq!(serial.report(), parallel.report());
+
+        let mut sampler = IndependentSampler::new(6);
+        assert_hits_match(&serial, &list, &mut sampler, 0.0);
+        assert_hits_match(&parallel, &list, &mut sampler, 0.0);
+    }
+
+    #[test]
+    fn test_unbounded_objects() {
+        let mut list = random_spheres(100);
+        list.add(Arc::new(Plane {
+            p1: Point3::new(0.0, -8.0, 0.0),
+            p2: Point3::new(0.0, -8.0, 0.0),
+            normal: Vec3::new(0.0, 1.0, 0.0),
+            material: Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        }));
+        let bvh = BVHNode::new(&list, 0.0, 1.0);
+
+        assert_eq!(bvh.report().unbounded_count, 1);
+        assert_eq!(bvh.report().primitive_count, 100);
+        assert!(bvh.get_b_box(0.0, 1.0).is_none());
+
+        // Far from every sphere, only the plane can be hit
+        let ray = Ray::new(
+            Point3::new(500.0, 0.0, 500.0),
+            Vec3::new(0.0, -1.0, 0.0),
+            0.0,
+        );
+        assert_eq!(bvh.hit(&ray, 0.001, f32::INFINITY).unwrap().t, 8.0);
+
+        let mut sampler = IndependentSampler::new(7);
+        assert_hits_match(&bvh, &list, &mut sampler, 0.0);
+    }
+
+    #[test]
+    fn test_coincident_centroids_still_split() {
+        // Nested spheres share a centroid, so no bin can separate them
+        let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let mut list = HitList::new();
+        for i in 0..10 {
+            list.add(Arc::new(Sphere {
+                center: Point3::new(0.0, 0.0, 0.0),
+                radius: 1.0 + i as f32,
+                material: material.clone(),
+            }));
+        }
+
+        let mut builder = BVHBuilder::new(0.0, 1.0);
+        builder.set_max_leaf_size(2);
+        let report = builder.build(&list).report();
+        assert!(report.max_leaf_size <= 2);
+        assert_eq!(report.primitive_count, 10);
+    }
+}
@@ -1,6 +1,5 @@
-use rand::{thread_rng, Rng};
-
 use crate::rays::Ray;
+use crate::sampling::Sampler;
 use crate::vectors::{Point3, Vec3};
 
 #[derive(Clone, Copy)]
@@ -60,14 +59,15 @@ impl Camera {
         self.shutter_close_time = shutter_close_time;
     }
 
-    pub fn get_ray(&self, s: f32, t: f32) -> Ray {
-        let rd = self.lens_radius * Vec3::random_in_unit_disk();
+    pub fn get_ray(&self, s: f32, t: f32, sampler: &mut dyn Sampler) -> Ray {
+        let rd = self.lens_radius * Vec3::random_in_unit_disk(sampler);
         let offset = self.u * rd.x() + self.v * rd.y();
 
         let origin = self.origin + offset;
         let direction = self.lower_left_corner + s * self.horizontal + t * self.vertical - origin;
 
-        let time = thread_rng().gen_range(self.shutter_open_time..self.shutter_close_time);
+        let time = self.shutter_open_time
+            + (self.shutter_close_time - self.shutter_open_time) * sampler.next_1d();
 
         Ray::new(origin, direction, time)
     }
@@ -0,0 +1,44 @@
+use image::ImageError;
+use std::{error::Error, fmt::Display, io};
+
+#[derive(Debug)]
+pub enum RenderError {
+    Io(io::Error),
+    Encoding(ImageError),
+    Decoding(ImageError),
+}
+
+impl Display for RenderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RenderError::Io(err) => write!(f, "I/O error: {}", err),
+            RenderError::Encoding(err) => write!(f, "could not encode image: {}", err),
+            RenderError::Decoding(err) => write!(f, "could not decode image: {}", err),
+        }
+    }
+}
+
+impl Error for RenderError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RenderError::Io(err) => Some(err),
+            RenderError::Encoding(err) => Some(err),
+            RenderError::Decoding(err) => Some(err),
+        }
+    }
+}
+
+impl From<io::Error> for RenderError {
+    fn from(err: io::Error) -> Self {
+        RenderError::Io(err)
+    }
+}
+
+impl From<ImageError> for RenderError {
+    fn from(err: ImageError) -> Self {
+        match err {
+            ImageError::IoError(err) => RenderError::Io(err),
+            err => RenderError::Encoding(err),
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use crate::{error::RenderError, rays::Color};
+use image::{Rgb, RgbImage};
+use std::path::Path;
+
+/// Gamma of stored images and colors, rendered colors are linear
+pub const GAMMA: f32 = 2.2;
+
+/// Decodes a gamma corrected channel in [0, 1] to linear.
+pub fn srgb_to_linear(value: f32) -> f32 {
+    value.powf(GAMMA)
+}
+
+/// Linear colors of an 8 bit image, row by row from the top left corner.
+pub fn linear_pixels(image: &RgbImage) -> Vec<Color> {
+    image
+        .pixels()
+        .map(|pixel| {
+            Color::new(
+                srgb_to_linear(pixel[0] as f32 / 255.0),
+                srgb_to_linear(pixel[1] as f32 / 255.0),
+                srgb_to_linear(pixel[2] as f32 / 255.0),
+            )
+        })
+        .collect()
+}
+
+/// Linear (not gamma corrected) color buffer, stored row by row from the top
+/// left corner of the image.
+#[derive(Clone)]
+pub struct FrameBuffer {
+    width: u32,
+    height: u32,
+    pixels: Vec<Color>,
+    sample_counts: Vec<u32>,
+}
+
+impl FrameBuffer {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![Color::default(); (width * height) as usize],
+            sample_counts: vec![0; (width * height) as usize],
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &[Color] {
+        &self.pixels
+    }
+
+    pub fn get_pixel(&self, x: u32, y: u32) -> Color {
+        self.pixels[self.index(x, y)]
+    }
+
+    pub fn put_pixel(&mut self, x: u32, y: u32, color: Color) {
+        let index = self.index(x, y);
+        self.pixels[index] = color;
+    }
+
+    /// Number of samples taken for the pixel so far
+    pub fn sample_count(&self, x: u32, y: u32) -> u32 {
+        self.sample_counts[self.index(x, y)]
+    }
+
+    pub fn set_sample_count(&mut self, x: u32, y: u32, count: u32) {
+        let index = self.index(x, y);
+        self.sample_counts[index] = count;
+    }
+
+    /// Visualizes where samples went, from black (fewest) through red and
+    /// yellow to white (most).
+    pub fn sample_heatmap(&self) -> RgbImage {
+        let max = self.sample_counts.iter().copied().max().unwrap_or(0).max(1) as f32;
+
+        RgbImage::from_fn(self.width, self.height, |x, y| {
+            let t = 3.0 * self.sample_count(x, y) as f32 / max;
+
+            Rgb([
+                (255.0 * t.clamp(0.0, 1.0)) as u8,
+                (255.0 * (t - 1.0).clamp(0.0, 1.0)) as u8,
+                (255.0 * (t - 2.0).clamp(0.0, 1.0)) as u8,
+            ])
+        })
+    }
+
+    pub fn to_rgb_image(&self) -> RgbImage {
+        RgbImage::from_fn(self.width, self.height, |x, y| {
+            Self::to_rgb(self.get_pixel(x, y))
+        })
+    }
+
+    pub fn save(&self, output: &dyn AsRef<Path>) -> Result<(), RenderError> {
+        self.to_rgb_image().save(output)?;
+
+        Ok(())
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        (y * self.width + x) as usize
+    }
+
+    fn to_rgb(color: Color) -> Rgb<u8> {
+        let (r, g, b) = (color.x(), color.y(), color.z());
+
+        // Color correction (gamma=2.2)
+        let r = r.powf(1.0 / GAMMA);
+        let g = g.powf(1.0 / GAMMA);
+        let b = b.powf(1.0 / GAMMA);
+
+        Rgb([
+            (256.0 * r.clamp(0.0, 0.999)) as u8,
+            (256.0 * g.clamp(0.0, 0.999)) as u8,
+            (256.0 * b.clamp(0.0, 0.999)) as u8,
+        ])
+    }
+}
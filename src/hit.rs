@@ -14,6 +14,12 @@ pub struct Hit {
     pub v: f32,
     pub is_front_facing: bool,
     pub material: Arc<dyn Material>,
+    /// Index of the part hit in objects made of many, like the faces of a
+    /// `TriangleMesh`. Textures use it to look up per-face data.
+    pub primitive: u32,
+    /// Weights of the second and third vertex of a triangle `primitive`,
+    /// whatever its texture coordinates are.
+    pub barycentrics: (f32, f32),
 }
 
 impl Hit {
@@ -40,21 +46,30 @@ impl Hit {
             normal,
             is_front_facing,
             material,
+            primitive: 0,
+            barycentrics: (0.0, 0.0),
         }
     }
 }
 
-pub trait Hittable {
+pub trait Hittable: Send + Sync {
     fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit>;
 
     fn get_b_box(&self, time0: f32, time1: f32) -> Option<AAAB>;
 }
 
-pub struct HitList(Vec<Arc<dyn Hittable>>);
+// Lets wrappers like `Transformed` hold shared objects
+impl<H: Hittable + ?Sized> Hittable for Arc<H> {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit> {
+        (**self).hit(ray, t_min, t_max)
+    }
 
-unsafe impl Send for HitList {}
+    fn get_b_box(&self, time0: f32, time1: f32) -> Option<AAAB> {
+        (**self).get_b_box(time0, time1)
+    }
+}
 
-unsafe impl Sync for HitList {}
+pub struct HitList(Vec<Arc<dyn Hittable>>);
 
 impl HitList {
     pub fn new() -> Self {
@@ -73,7 +88,7 @@ impl HitList {
         self.0.is_empty()
     }
 
-    pub fn iter(&self) -> std::slice::Iter<Arc<dyn Hittable>> {
+    pub fn iter(&self) -> std::slice::Iter<'_, Arc<dyn Hittable>> {
         self.0.iter()
     }
 
@@ -85,6 +100,14 @@ impl HitList {
         self.0.get(index)
     }
 
+    /// Box around the objects that have one, skipping unbounded ones like
+    /// planes. `None` only if no object is bounded.
+    pub fn bounded_b_box(&self, time0: f32, time1: f32) -> Option<AAAB> {
+        self.iter()
+            .filter_map(|object| object.get_b_box(time0, time1))
+            .reduce(AAAB::new_surrounding_box)
+    }
+
     pub fn sort_by<F>(&mut self, compare: F)
     where
         F: FnMut(&Arc<dyn Hittable>, &Arc<dyn Hittable>) -> Ordering,
@@ -114,6 +137,9 @@ impl Hittable for HitList {
         current_hit
     }
 
+    /// `None` as soon as one object is unbounded, since no box encloses it. A
+    /// `BVHNode` built over the list keeps those objects aside instead, and
+    /// `bounded_b_box` ignores them.
     fn get_b_box(&self, time0: f32, time1: f32) -> Option<AAAB> {
         if self.is_empty() {
             return None;
@@ -135,3 +161,36 @@ impl Hittable for HitList {
         curr_box
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        materials::lambertian::Lambertian,
+        objects::{plane::Plane, sphere::Sphere},
+        rays::Color,
+    };
+
+    #[test]
+    fn test_bounded_b_box_skips_planes() {
+        let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let mut list = HitList::new();
+        list.add(Arc::new(Plane {
+            p1: Point3::new(0.0, -1.0, 0.0),
+            p2: Point3::new(0.0, -1.0, 0.0),
+            normal: Vec3::new(0.0, 1.0, 0.0),
+            material: material.clone(),
+        }));
+        assert!(list.bounded_b_box(0.0, 1.0).is_none());
+
+        list.add(Arc::new(Sphere {
+            center: Point3::new(2.0, 0.0, 0.0),
+            radius: 1.0,
+            material,
+        }));
+        assert!(list.get_b_box(0.0, 1.0).is_none());
+        let b_box = list.bounded_b_box(0.0, 1.0).unwrap();
+        assert_eq!(b_box.min(), Point3::new(1.0, -1.0, -1.0));
+        assert_eq!(b_box.max(), Point3::new(3.0, 1.0, 1.0));
+    }
+}
@@ -4,176 +4,355 @@
 static GLOBAL: jemallocator::Jemalloc = jemallocator::Jemalloc;
 
 use crate::hit::Hittable;
+use adaptive::{AdaptiveSampling, PixelEstimate};
+use backgrounds::{gradient::GradientBackground, Background};
 use camera::Camera;
-use image::{Rgb, RgbImage};
-use indicatif::ProgressStyle;
-use indicatif::{MultiProgress, ProgressBar};
-use rand::{thread_rng, Rng};
+use error::RenderError;
+use framebuffer::FrameBuffer;
+use indicatif::{ProgressBar, ProgressStyle};
 use rays::{Color, Ray};
-use std::{f32::INFINITY, path::Path, sync::Arc};
+use sampling::{Sampler, SamplerKind};
+use std::{path::Path, sync::Arc};
 use std::{sync::mpsc, thread};
+use tiles::{generate_tiles, Tile, TileOrder, TileQueue, DEFAULT_TILE_SIZE};
 
 pub mod aabb;
+pub mod adaptive;
+pub mod backgrounds;
 pub mod bvh;
 pub mod camera;
+pub mod error;
+pub mod framebuffer;
 pub mod hit;
+pub mod loaders;
 pub mod materials;
 pub mod objects;
 pub mod rays;
+pub mod roots;
+pub mod sampling;
 pub mod textures;
+pub mod tiles;
 pub mod vectors;
 
 const MAX_DEPTH: u32 = 50;
 const BIAS: f32 = 0.001;
+const DEFAULT_SEED: u64 = 0;
 
-#[derive(Copy, Clone)]
+#[derive(Clone)]
 pub struct Raytracer {
     height: u32,
     width: u32,
     camera: Camera,
     sample_size: u32,
+    tile_size: u32,
+    tile_order: TileOrder,
+    adaptive_sampling: Option<AdaptiveSampling>,
+    seed: u64,
+    sampler: SamplerKind,
+    background: Arc<dyn Background>,
 }
 
-impl<'a> Raytracer {
+impl Raytracer {
     pub fn new(width: u32, height: u32, camera: Camera, sample_size: u32) -> Self {
         Self {
             height,
             width,
             camera,
             sample_size,
+            tile_size: DEFAULT_TILE_SIZE,
+            tile_order: TileOrder::Scanline,
+            adaptive_sampling: None,
+            seed: DEFAULT_SEED,
+            sampler: SamplerKind::Independent,
+            background: Arc::new(GradientBackground::default()),
         }
     }
 
-    pub fn render<T>(self, scene: T, output: &'a dyn AsRef<Path>)
-    where
-        T: Hittable + Send + Sync + 'static,
-    {
-        // Init image
-        let mut img = RgbImage::new(self.width, self.height);
-        // Chunks
-        println!("Preparing chunks...");
+    /// Lighting for rays that escape the scene, a white to blue gradient by
+    /// default. Use a black `SolidBackground` for scenes lit only by emissive
+    /// materials.
+    pub fn set_background(&mut self, background: Arc<dyn Background>) {
+        self.background = background;
+    }
+
+    pub fn set_sampler(&mut self, sampler: SamplerKind) {
+        self.sampler = sampler;
+    }
+
+    /// Renders with the same seed produce the same image, regardless of the
+    /// number of threads or the tile settings.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
 
-        let chunks = self.image_chunks();
+    pub fn set_tile_size(&mut self, tile_size: u32) {
+        self.tile_size = tile_size.max(1);
+    }
 
-        println!("Chunks prepared!");
+    pub fn set_tile_order(&mut self, tile_order: TileOrder) {
+        self.tile_order = tile_order;
+    }
 
-        // Progress bar init
-        let multibar = MultiProgress::new();
-        let style = ProgressStyle::default_bar()
-            .template("[{elapsed_precise}] [{bar:40.cyan/blue}] {pos:>7}/{len:7} {msg}")
-            .progress_chars("=> ");
+    /// Replaces the fixed `sample_size` with a per pixel budget that stops
+    /// sampling pixels once they stop changing. Not used in progressive renders.
+    pub fn set_adaptive_sampling(&mut self, adaptive_sampling: AdaptiveSampling) {
+        self.adaptive_sampling = Some(adaptive_sampling);
+    }
 
-        println!("\nStarting workers...\n");
+    pub fn render<T>(&self, scene: &T, output: &dyn AsRef<Path>) -> Result<(), RenderError>
+    where
+        T: Hittable,
+    {
+        let frame = self.render_to_buffer(scene);
 
-        let (tx, rx) = mpsc::channel::<Vec<(u32, u32, image::Rgb<u8>)>>();
+        print!("\nSaving image... ");
 
-        let scene_arc = Arc::new(scene);
-        let tx_arc = Arc::new(tx);
-        for (_, chunk) in chunks.into_iter().enumerate() {
-            let scene_arc = scene_arc.clone();
-            let sender = mpsc::Sender::clone(&tx_arc);
+        frame.save(output)?;
 
-            let progress = multibar.add(ProgressBar::new(chunk.len() as u64));
-            progress.set_style(style.clone());
+        println!("Done!");
 
-            thread::spawn(move || {
-                let mut rng = thread_rng();
-                let pixels = chunk
-                    .into_iter()
-                    .map(|(y, x)| {
-                        progress.inc(1);
+        Ok(())
+    }
 
-                        let mut color = Color::default();
-                        for _ in 0..self.sample_size {
-                            let u = (x as f32 + rng.gen::<f32>()) / (self.width - 1) as f32;
-                            let v = (y as f32 + rng.gen::<f32>()) / (self.height - 1) as f32;
+    pub fn render_to_buffer<T>(&self, scene: &T) -> FrameBuffer
+    where
+        T: Hittable,
+    {
+        let mut frame = FrameBuffer::new(self.width, self.height);
 
-                            let ray = self.camera.get_ray(u, v);
-                            color += Self::raytrace(ray, &*scene_arc, MAX_DEPTH);
-                        }
+        let sampling = self
+            .adaptive_sampling
+            .unwrap_or_else(|| AdaptiveSampling::fixed(self.sample_size));
 
-                        let color = Self::calculate_pixel_color(color, self.sample_size);
+        let progress = self.progress_bar(1);
+        self.render_pass(scene, sampling, 0, &progress, |x, y, estimate| {
+            frame.put_pixel(x, y, estimate.mean());
+            frame.set_sample_count(x, y, estimate.count());
+        });
+        progress.finish_with_message("Done!");
 
-                        (x, self.height - 1 - y, color)
-                    })
-                    .collect();
+        frame
+    }
 
-                sender.send(pixels).unwrap();
-                progress.finish_with_message("Done!");
-                drop(sender);
+    /// Renders the image one sample per pixel at a time, for up to `sample_size`
+    /// passes. After every pass `on_pass` is called with the number of completed
+    /// passes and the image averaged so far; returning `false` stops the render.
+    pub fn render_progressive<T, F>(&self, scene: &T, mut on_pass: F) -> FrameBuffer
+    where
+        T: Hittable,
+        F: FnMut(u32, &FrameBuffer) -> bool,
+    {
+        let mut frame = FrameBuffer::new(self.width, self.height);
+
+        let progress = self.progress_bar(self.sample_size);
+        for pass in 1..=self.sample_size {
+            let sampling = AdaptiveSampling::fixed(1);
+            self.render_pass(scene, sampling, pass - 1, &progress, |x, y, estimate| {
+                // Running average over the passes
+                let average = frame.get_pixel(x, y);
+                frame.put_pixel(x, y, average + (estimate.mean() - average) / pass as f32);
+                frame.set_sample_count(x, y, pass);
             });
-        }
-        drop(tx_arc);
 
-        multibar.join().unwrap();
-
-        for pixels in rx {
-            for pixel in pixels {
-                img.put_pixel(pixel.0, pixel.1, pixel.2);
+            if !on_pass(pass, &frame) {
+                break;
             }
         }
+        progress.finish_with_message("Done!");
 
-        print!("\nSaving image... ");
+        frame
+    }
 
-        img.save(output).expect("Could not save image");
+    fn progress_bar(&self, passes: u32) -> ProgressBar {
+        let progress = ProgressBar::new(self.width as u64 * self.height as u64 * passes as u64);
+        progress.set_style(
+            ProgressStyle::default_bar()
+                .template("[{elapsed_precise}] [{bar:40.cyan/blue}] {pos:>7}/{len:7} {msg}")
+                .progress_chars("=> "),
+        );
 
-        println!("Done!");
+        progress
     }
 
-    fn image_chunks(&self) -> Vec<Vec<(u32, u32)>> {
-        let threads = num_cpus::get();
+    // Renders every pixel of the image, calling `write` with the pixel's
+    // estimate as tiles come back from the workers. Sample indices start at
+    // `first_sample`, so consecutive passes do not repeat the same samples.
+    fn render_pass<T, F>(
+        &self,
+        scene: &T,
+        sampling: AdaptiveSampling,
+        first_sample: u32,
+        progress: &ProgressBar,
+        mut write: F,
+    ) where
+        T: Hittable,
+        F: FnMut(u32, u32, PixelEstimate),
+    {
+        let queue = TileQueue::new(generate_tiles(
+            self.width,
+            self.height,
+            self.tile_size,
+            self.tile_order,
+        ));
+
+        let (tx, rx) = mpsc::channel::<(Tile, Vec<PixelEstimate>)>();
+
+        thread::scope(|s| {
+            for _ in 0..num_cpus::get() {
+                let sender = tx.clone();
+                let queue = &queue;
+
+                s.spawn(move || {
+                    let mut sampler = self.sampler.build(self.seed, self.samples_per_pixel());
+                    while let Some(tile) = queue.pop() {
+                        let pixels =
+                            self.render_tile(scene, tile, &sampling, first_sample, &mut *sampler);
+                        sender.send((tile, pixels)).unwrap();
+                    }
+                });
+            }
+            drop(tx);
 
-        (0..self.height)
-            .collect::<Vec<u32>>()
-            .iter()
-            .map(|h| {
-                (0..self.width)
-                    .map(|w| (*h, w))
-                    .collect::<Vec<(u32, u32)>>()
-            })
-            .enumerate()
-            .fold(vec![Vec::new(); threads], |mut acc, (i, band)| {
-                acc.get_mut(i % threads).unwrap().extend(band);
-                acc
-            })
+            // Tiles are written out as soon as any worker finishes them
+            for (tile, pixels) in rx {
+                for ((x, y), estimate) in tile.pixels().zip(pixels) {
+                    write(x, y, estimate);
+                }
+                progress.inc(tile.len() as u64);
+            }
+        });
     }
 
-    fn calculate_pixel_color(color: Color, sample_size: u32) -> Rgb<u8> {
-        let scale = 1.0 / sample_size as f32;
-        let (r, g, b) = (color.x(), color.y(), color.z());
+    // Sample budget of a pixel over the whole render, samplers stratify over it
+    fn samples_per_pixel(&self) -> u32 {
+        self.adaptive_sampling
+            .map_or(self.sample_size, |sampling| sampling.max_samples)
+    }
 
-        // Color correction (gamma=2.2)
-        let r = (scale * r).powf(1.0 / 2.2);
-        let g = (scale * g).powf(1.0 / 2.2);
-        let b = (scale * b).powf(1.0 / 2.2);
+    fn render_tile<T>(
+        &self,
+        scene: &T,
+        tile: Tile,
+        sampling: &AdaptiveSampling,
+        first_sample: u32,
+        sampler: &mut dyn Sampler,
+    ) -> Vec<PixelEstimate>
+    where
+        T: Hittable,
+    {
+        tile.pixels()
+            .map(|(x, y)| {
+                let mut estimate = PixelEstimate::default();
+                while !estimate.is_done(sampling) {
+                    sampler.start_pixel_sample(x, y, first_sample + estimate.count());
+
+                    // Image rows go top to bottom, the camera's v axis goes up
+                    let (jitter_u, jitter_v) = sampler.next_2d();
+                    let u = (x as f32 + jitter_u) / (self.width - 1) as f32;
+                    let v = ((self.height - 1 - y) as f32 + jitter_v) / (self.height - 1) as f32;
+
+                    let ray = self.camera.get_ray(u, v, sampler);
+                    estimate.add(self.raytrace(ray, scene, sampler, MAX_DEPTH));
+                }
 
-        Rgb([
-            (256.0 * r.clamp(0.0, 0.999)) as u8,
-            (256.0 * g.clamp(0.0, 0.999)) as u8,
-            (256.0 * b.clamp(0.0, 0.999)) as u8,
-        ])
+                estimate
+            })
+            .collect()
     }
 
-    fn raytrace(ray: Ray, scene: &dyn Hittable, depth: u32) -> Color {
+    fn raytrace(
+        &self,
+        ray: Ray,
+        scene: &dyn Hittable,
+        sampler: &mut dyn Sampler,
+        depth: u32,
+    ) -> Color {
         if depth == 0 {
             return Color::default();
         }
 
-        if let Some(hit) = scene.hit(&ray, 0.0 + BIAS, INFINITY) {
-            return match hit.material.scatter(&ray, &hit) {
+        if let Some(hit) = scene.hit(&ray, 0.0 + BIAS, f32::INFINITY) {
+            let emitted = hit.material.emit(hit.u, hit.v, &hit.point);
+
+            return match hit.material.scatter(&ray, &hit, sampler) {
                 Some((scattered_ray, attenuatuion)) => {
-                    attenuatuion * Self::raytrace(scattered_ray, scene, depth - 1)
+                    emitted + attenuatuion * self.raytrace(scattered_ray, scene, sampler, depth - 1)
                 }
-                None => Color::default(),
+                None => emitted,
             };
         }
 
-        // Background sky gradient
-        let unit_direction = ray.direction().unit_vector();
-        let t = 0.5 * (unit_direction.y() + 1.0);
-        let start_value = Color::new(1.0, 1.0, 1.0);
-        let end_value = Color::new(0.5, 0.7, 1.0);
+        self.background.value(&ray)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        hit::HitList,
+        materials::{dielectric::Dielectric, lambertian::Lambertian, metal::Metal},
+        objects::sphere::Sphere,
+        vectors::{Point3, Vec3},
+    };
+    use std::sync::Arc;
+
+    fn test_scene() -> (Camera, HitList) {
+        let camera = Camera::new(
+            Point3::new(0.0, 0.0, 1.0),
+            Point3::new(0.0, 0.0, -1.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            60.0,
+            2.0,
+            0.1,
+            2.0,
+        );
+
+        let mut scene = HitList::new();
+        scene.add(Arc::new(Sphere {
+            center: Point3::new(-0.6, 0.0, -1.0),
+            radius: 0.5,
+            material: Arc::new(Lambertian::new(Color::new(0.7, 0.3, 0.3))),
+        }));
+        scene.add(Arc::new(Sphere {
+            center: Point3::new(0.6, 0.0, -1.0),
+            radius: 0.5,
+            material: Arc::new(Dielectric::new(1.5)),
+        }));
+        scene.add(Arc::new(Sphere {
+            center: Point3::new(0.0, -100.5, -1.0),
+            radius: 100.0,
+            material: Arc::new(Metal::new(Color::new(0.8, 0.8, 0.8), 0.3)),
+        }));
+
+        (camera, scene)
+    }
+
+    #[test]
+    fn test_same_seed_renders_identical_images() {
+        let (camera, scene) = test_scene();
+
+        let mut raytracer = Raytracer::new(24, 12, camera, 4);
+        raytracer.set_seed(7);
+        let first = raytracer.render_to_buffer(&scene);
+
+        raytracer.set_tile_size(5);
+        raytracer.set_tile_order(TileOrder::Hilbert);
+        let second = raytracer.render_to_buffer(&scene);
+
+        assert_eq!(first.pixels(), second.pixels());
+    }
+
+    #[test]
+    fn test_different_seeds_render_different_images() {
+        let (camera, scene) = test_scene();
+
+        let mut raytracer = Raytracer::new(24, 12, camera, 4);
+        raytracer.set_seed(1);
+        let first = raytracer.render_to_buffer(&scene);
+        raytracer.set_seed(2);
+        let second = raytracer.render_to_buffer(&scene);
 
-        (1.0 - t) * start_value + t * end_value
+        assert_ne!(first.pixels(), second.pixels());
     }
 }
@@ -0,0 +1,82 @@
+pub mod obj;
+pub mod ply;
+pub mod stl;
+
+use crate::error::RenderError;
+use std::{
+    error::Error,
+    fmt::Display,
+    io,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+    /// Malformed data in a binary file, where lines mean nothing
+    Format {
+        path: PathBuf,
+        message: String,
+    },
+    Texture {
+        path: PathBuf,
+        source: RenderError,
+    },
+}
+
+impl LoadError {
+    pub fn io(path: &Path, source: io::Error) -> Self {
+        LoadError::Io {
+            path: path.to_owned(),
+            source,
+        }
+    }
+
+    pub fn format(path: &Path, message: impl Into<String>) -> Self {
+        LoadError::Format {
+            path: path.to_owned(),
+            message: message.into(),
+        }
+    }
+
+    pub fn parse(path: &Path, line: usize, message: impl Into<String>) -> Self {
+        LoadError::Parse {
+            path: path.to_owned(),
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            LoadError::Parse {
+                path,
+                line,
+                message,
+            } => write!(f, "{}:{}: {}", path.display(), line, message),
+            LoadError::Format { path, message } => write!(f, "{}: {}", path.display(), message),
+            LoadError::Texture { path, source } => write!(f, "{}: {}", path.display(), source),
+        }
+    }
+}
+
+impl Error for LoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LoadError::Io { source, .. } => Some(source),
+            LoadError::Parse { .. } | LoadError::Format { .. } => None,
+            LoadError::Texture { source, .. } => Some(source),
+        }
+    }
+}
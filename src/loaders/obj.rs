@@ -0,0 +1,483 @@
+use crate::{
+    hit::HitList,
+    materials::{
+        dielectric::Dielectric, diffuse_light::DiffuseLight, lambertian::Lambertian, metal::Metal,
+        Material,
+    },
+    objects::mesh::{MeshData, TriangleMesh},
+    rays::Color,
+    textures::image_texture::ImageTexture,
+    vectors::{Point3, Vec3},
+};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+    str::SplitWhitespace,
+    sync::Arc,
+};
+
+use super::LoadError;
+
+const DEFAULT_COLOR: f32 = 0.8;
+const DEFAULT_REFRACTIVE_INDEX: f32 = 1.5;
+
+/// Material as described in an `.mtl` file
+#[derive(Clone, Debug, PartialEq)]
+pub struct MtlMaterial {
+    pub name: String,
+    /// Kd
+    pub diffuse: Color,
+    /// Ks
+    pub specular: Color,
+    /// Ns
+    pub shininess: f32,
+    /// Ni
+    pub refractive_index: f32,
+    /// d, or 1 - Tr
+    pub dissolve: f32,
+    /// Ke
+    pub emission: Color,
+    /// map_Kd, already resolved against the directory of the `.mtl` file
+    pub diffuse_map: Option<PathBuf>,
+}
+
+impl MtlMaterial {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            diffuse: Color::new(DEFAULT_COLOR, DEFAULT_COLOR, DEFAULT_COLOR),
+            specular: Color::default(),
+            shininess: 0.0,
+            refractive_index: DEFAULT_REFRACTIVE_INDEX,
+            dissolve: 1.0,
+            emission: Color::default(),
+            diffuse_map: None,
+        }
+    }
+
+    /// Picks the closest material this renderer has: emissive materials become
+    /// lights, transparent ones glass, mostly specular ones metal (rougher for
+    /// lower shininess) and everything else Lambertian.
+    pub fn to_material(&self) -> Result<Arc<dyn Material>, LoadError> {
+        if max_component(self.emission) > 0.0 {
+            return Ok(Arc::new(DiffuseLight::new(self.emission, 1.0)));
+        }
+
+        if self.dissolve < 1.0 {
+            return Ok(Arc::new(Dielectric::new(self.refractive_index)));
+        }
+
+        if max_component(self.specular) > max_component(self.diffuse) {
+            // Blinn-Phong exponent to roughness
+            let fuzz = (2.0 / (self.shininess + 2.0)).sqrt();
+            return Ok(Arc::new(Metal::new(self.specular, fuzz)));
+        }
+
+        if let Some(path) = &self.diffuse_map {
+            let texture = ImageTexture::load(path).map_err(|source| LoadError::Texture {
+                path: path.clone(),
+                source,
+            })?;
+            return Ok(Arc::new(Lambertian::with_texture(Arc::new(texture))));
+        }
+
+        Ok(Arc::new(Lambertian::new(self.diffuse)))
+    }
+}
+
+pub fn load_mtl(path: &dyn AsRef<Path>) -> Result<Vec<MtlMaterial>, LoadError> {
+    let path = path.as_ref();
+    let file = File::open(path).map_err(|err| LoadError::io(path, err))?;
+
+    read_mtl(BufReader::new(file), path)
+}
+
+/// Parses `.mtl` data, `path` is used for error messages and to resolve
+/// texture paths.
+pub fn read_mtl<R: BufRead>(reader: R, path: &Path) -> Result<Vec<MtlMaterial>, LoadError> {
+    let directory = path.parent().unwrap_or_else(|| Path::new(""));
+    let mut materials: Vec<MtlMaterial> = Vec::new();
+
+    for (index, line) in reader.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.map_err(|err| LoadError::io(path, err))?;
+        let mut tokens = Tokens::new(&line, path, line_number);
+
+        let keyword = match tokens.next() {
+            Some(keyword) => keyword,
+            None => continue,
+        };
+
+        if keyword == "newmtl" {
+            materials.push(MtlMaterial::new(tokens.rest()?));
+            continue;
+        }
+
+        let material = match materials.last_mut() {
+            Some(material) => material,
+            None => return Err(tokens.error(format!("'{}' before any newmtl", keyword))),
+        };
+
+        match keyword {
+            "Kd" => material.diffuse = tokens.color()?,
+            "Ks" => material.specular = tokens.color()?,
+            "Ke" => material.emission = tokens.color()?,
+            "Ns" => material.shininess = tokens.float()?,
+            "Ni" => material.refractive_index = tokens.float()?,
+            "d" => material.dissolve = tokens.float()?,
+            "Tr" => material.dissolve = 1.0 - tokens.float()?,
+            // Options before the file name are not supported, the last token is the file
+            "map_Kd" => {
+                let file = tokens.file_name()?;
+                material.diffuse_map = Some(directory.join(file));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(materials)
+}
+
+/// Loads an `.obj` file and the `.mtl` files it references. Each group or
+/// material change starts a new `TriangleMesh`, so the returned list is
+/// ready for `BVHNode::new`.
+pub fn load_obj(path: &dyn AsRef<Path>) -> Result<HitList, LoadError> {
+    let path = path.as_ref();
+    let file = File::open(path).map_err(|err| LoadError::io(path, err))?;
+
+    read_obj(BufReader::new(file), path)
+}
+
+/// Parses `.obj` data, `path` is used for error messages and to resolve
+/// `mtllib` paths.
+pub fn read_obj<R: BufRead>(reader: R, path: &Path) -> Result<HitList, LoadError> {
+    let directory = path.parent().unwrap_or_else(|| Path::new(""));
+    let default_material: Arc<dyn Material> = Arc::new(Lambertian::new(Color::new(
+        DEFAULT_COLOR,
+        DEFAULT_COLOR,
+        DEFAULT_COLOR,
+    )));
+
+    let mut positions: Vec<Point3<f32>> = Vec::new();
+    let mut uvs: Vec<(f32, f32)> = Vec::new();
+    let mut normals: Vec<Vec3<f32>> = Vec::new();
+    let mut materials: HashMap<String, Arc<dyn Material>> = HashMap::new();
+
+    let mut list = HitList::new();
+    let mut mesh = MeshBuilder::new(default_material.clone());
+
+    for (index, line) in reader.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.map_err(|err| LoadError::io(path, err))?;
+        let mut tokens = Tokens::new(&line, path, line_number);
+
+        match tokens.next() {
+            Some("v") => positions.push(tokens.vector()?),
+            Some("vn") => normals.push(tokens.vector()?),
+            Some("vt") => {
+                let u = tokens.float()?;
+                let v = tokens.optional_float()?.unwrap_or(0.0);
+                uvs.push((u, v));
+            }
+            Some("f") => {
+                let vertices: Vec<&str> = tokens.by_ref().collect();
+                let mut corners = Vec::new();
+                for token in vertices {
+                    let mut references = token.split('/');
+                    let position = references.next().unwrap_or("");
+                    let position = resolve_index(position, positions.len(), &tokens, "vertex")?;
+                    let uv = match references.next() {
+                        Some(uv) if !uv.is_empty() => {
+                            Some(resolve_index(uv, uvs.len(), &tokens, "texture coordinate")?)
+                        }
+                        _ => None,
+                    };
+                    let normal = match references.next() {
+                        Some(normal) if !normal.is_empty() => {
+                            Some(resolve_index(normal, normals.len(), &tokens, "normal")?)
+                        }
+                        _ => None,
+                    };
+
+                    corners.push(mesh.vertex((position, uv, normal), &positions, &uvs, &normals));
+                }
+
+                if corners.len() < 3 {
+                    return Err(tokens.error("face with less than 3 vertices"));
+                }
+
+                // Fan triangulation, fine for the convex polygons OBJ files contain
+                for i in 1..corners.len() - 1 {
+                    mesh.data
+                        .indices
+                        .push([corners[0], corners[i], corners[i + 1]]);
+                }
+            }
+            Some("g") | Some("o") => {
+                mesh = mesh.finish(&mut list);
+            }
+            Some("usemtl") => {
+                let name = tokens.rest()?;
+                let material = materials
+                    .get(name)
+                    .cloned()
+                    .unwrap_or_else(|| default_material.clone());
+                mesh = mesh.finish(&mut list);
+                mesh.material = material;
+            }
+            Some("mtllib") => {
+                for file in tokens.by_ref() {
+                    for mtl in load_mtl(&directory.join(file))? {
+                        materials.insert(mtl.name.clone(), mtl.to_material()?);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    mesh.finish(&mut list);
+
+    Ok(list)
+}
+
+// Faces of the current group, with the OBJ's separate position/uv/normal
+// indices merged into single vertex indices
+struct MeshBuilder {
+    data: MeshData,
+    material: Arc<dyn Material>,
+    vertices: HashMap<(usize, Option<usize>, Option<usize>), u32>,
+    has_uvs: bool,
+    missing_normals: bool,
+}
+
+impl MeshBuilder {
+    fn new(material: Arc<dyn Material>) -> Self {
+        Self {
+            data: MeshData::default(),
+            material,
+            vertices: HashMap::new(),
+            has_uvs: false,
+            missing_normals: false,
+        }
+    }
+
+    fn vertex(
+        &mut self,
+        key: (usize, Option<usize>, Option<usize>),
+        positions: &[Point3<f32>],
+        uvs: &[(f32, f32)],
+        normals: &[Vec3<f32>],
+    ) -> u32 {
+        if let Some(&index) = self.vertices.get(&key) {
+            return index;
+        }
+
+        let (position, uv, normal) = key;
+        let index = self.data.positions.len() as u32;
+        self.data.positions.push(positions[position]);
+        self.data.uvs.push(uv.map_or((0.0, 0.0), |uv| uvs[uv]));
+        self.data
+            .normals
+            .push(normal.map_or(Vec3::default(), |normal| normals[normal]));
+
+        self.has_uvs |= uv.is_some();
+        self.missing_normals |= normal.is_none();
+        self.vertices.insert(key, index);
+
+        index
+    }
+
+    // Adds the mesh to the list, if it has any faces, and starts a new one
+    // with the same material
+    fn finish(mut self, list: &mut HitList) -> Self {
+        let material = self.material.clone();
+
+        if !self.data.indices.is_empty() {
+            if !self.has_uvs {
+                self.data.uvs.clear();
+            }
+            if self.missing_normals {
+                // Flat shading, unless every vertex has a normal
+                self.data.normals.clear();
+            }
+            list.add(Arc::new(TriangleMesh::new(self.data, self.material)));
+        }
+
+        Self::new(material)
+    }
+}
+
+// Turns a 1-based (or negative, relative to the end) OBJ index into a
+// 0-based one
+fn resolve_index(
+    token: &str,
+    count: usize,
+    tokens: &Tokens,
+    what: &str,
+) -> Result<usize, LoadError> {
+    let index: i64 = token
+        .parse()
+        .map_err(|_| tokens.error(format!("invalid {} index '{}'", what, token)))?;
+
+    let resolved = match index {
+        0 => None,
+        index if index > 0 => Some(index - 1),
+        index => Some(count as i64 + index),
+    };
+
+    match resolved {
+        Some(resolved) if resolved >= 0 && (resolved as usize) < count => Ok(resolved as usize),
+        _ => Err(tokens.error(format!("{} index {} out of range", what, index))),
+    }
+}
+
+fn max_component(color: Color) -> f32 {
+    color.x().max(color.y()).max(color.z())
+}
+
+// Whitespace separated tokens of one line, without the comment
+struct Tokens<'a> {
+    tokens: SplitWhitespace<'a>,
+    line: &'a str,
+    path: &'a Path,
+    line_number: usize,
+}
+
+impl<'a> Tokens<'a> {
+    fn new(line: &'a str, path: &'a Path, line_number: usize) -> Self {
+        let line = line.split('#').next().unwrap_or("").trim();
+
+        Self {
+            tokens: line.split_whitespace(),
+            line,
+            path,
+            line_number,
+        }
+    }
+
+    fn error(&self, message: impl Into<String>) -> LoadError {
+        LoadError::parse(self.path, self.line_number, message)
+    }
+
+    fn optional_float(&mut self) -> Result<Option<f32>, LoadError> {
+        match self.tokens.next() {
+            Some(token) => token
+                .parse()
+                .map(Some)
+                .map_err(|_| self.error(format!("invalid number '{}'", token))),
+            None => Ok(None),
+        }
+    }
+
+    fn float(&mut self) -> Result<f32, LoadError> {
+        self.optional_float()?
+            .ok_or_else(|| self.error("missing number"))
+    }
+
+    fn vector(&mut self) -> Result<Vec3<f32>, LoadError> {
+        Ok(Vec3::new(self.float()?, self.float()?, self.float()?))
+    }
+
+    fn color(&mut self) -> Result<Color, LoadError> {
+        let r = self.float()?;
+        // A single value means grey
+        match self.optional_float()? {
+            Some(g) => Ok(Color::new(r, g, self.float()?)),
+            None => Ok(Color::new(r, r, r)),
+        }
+    }
+
+    // Everything after the keyword, for names that may contain spaces
+    fn rest(&mut self) -> Result<&'a str, LoadError> {
+        let keyword = self.line.split_whitespace().next().unwrap_or("");
+        let rest = self.line[keyword.len()..].trim();
+        if rest.is_empty() {
+            return Err(self.error(format!("missing name after '{}'", keyword)));
+        }
+
+        Ok(rest)
+    }
+
+    fn file_name(&mut self) -> Result<&'a str, LoadError> {
+        self.tokens
+            .by_ref()
+            .last()
+            .ok_or_else(|| self.error("missing file name"))
+    }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.tokens.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{hit::Hittable, rays::Ray};
+
+    fn read(source: &str) -> Result<HitList, LoadError> {
+        read_obj(source.as_bytes(), Path::new("test.obj"))
+    }
+
+    #[test]
+    fn test_quad_is_triangulated() {
+        let list = read(
+            "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n\
+             vt 0 0\nvt 1 0\nvt 1 1\nvt 0 1\n\
+             f 1/1 2/2 3/3 4/4\n",
+        )
+        .unwrap();
+
+        assert_eq!(list.len(), 1);
+        let ray = Ray::new(Point3::new(0.25, 0.75, 1.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let hit = list.hit(&ray, 0.001, f32::INFINITY).unwrap();
+        assert!((hit.u - 0.25).abs() < 1e-5 && (hit.v - 0.75).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_negative_indices_and_groups() {
+        let list = read(
+            "g first\nv 0 0 0\nv 1 0 0\nv 0 1 0\nf -3 -2 -1\n\
+             g second\nv 0 0 1\nv 1 0 1\nv 0 1 1\nvn 0 0 1\nf -3//1 -2//1 -1//1\n",
+        )
+        .unwrap();
+
+        assert_eq!(list.len(), 2);
+    }
+
+    #[test]
+    fn test_errors_have_line_numbers() {
+        let error = read("v 0 0 0\nv 1 0 0\nf 1 2 7\n").err().unwrap();
+        assert_eq!(error.to_string(), "test.obj:3: vertex index 7 out of range");
+
+        let error = read("# comment\nv 0 zero 0\n").err().unwrap();
+        assert_eq!(error.to_string(), "test.obj:2: invalid number 'zero'");
+    }
+
+    #[test]
+    fn test_mtl_parsing() {
+        let materials = read_mtl(
+            "newmtl glass\nKd 0.1 0.2 0.3\nNi 1.33\nd 0.2\n\
+             newmtl lamp\nKe 4\nmap_Kd -bm 1 textures/lamp.png\n"
+                .as_bytes(),
+            Path::new("scene/test.mtl"),
+        )
+        .unwrap();
+
+        assert_eq!(materials.len(), 2);
+        assert_eq!(materials[0].diffuse, Color::new(0.1, 0.2, 0.3));
+        assert_eq!(materials[0].refractive_index, 1.33);
+        assert_eq!(materials[0].dissolve, 0.2);
+        assert_eq!(materials[1].emission, Color::new(4.0, 4.0, 4.0));
+        assert_eq!(
+            materials[1].diffuse_map,
+            Some(PathBuf::from("scene/textures/lamp.png"))
+        );
+    }
+}
@@ -0,0 +1,551 @@
+use crate::{framebuffer::srgb_to_linear, objects::mesh::MeshData, rays::Color, vectors::Vec3};
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Lines, Read},
+    path::Path,
+};
+
+use super::LoadError;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Format {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ScalarType {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl ScalarType {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "char" | "int8" => Some(ScalarType::I8),
+            "uchar" | "uint8" => Some(ScalarType::U8),
+            "short" | "int16" => Some(ScalarType::I16),
+            "ushort" | "uint16" => Some(ScalarType::U16),
+            "int" | "int32" => Some(ScalarType::I32),
+            "uint" | "uint32" => Some(ScalarType::U32),
+            "float" | "float32" => Some(ScalarType::F32),
+            "double" | "float64" => Some(ScalarType::F64),
+            _ => None,
+        }
+    }
+
+    fn size(self) -> usize {
+        match self {
+            ScalarType::I8 | ScalarType::U8 => 1,
+            ScalarType::I16 | ScalarType::U16 => 2,
+            ScalarType::I32 | ScalarType::U32 | ScalarType::F32 => 4,
+            ScalarType::F64 => 8,
+        }
+    }
+
+    // Largest value of a color channel, floats are already in [0, 1]
+    fn color_scale(self) -> f64 {
+        match self {
+            ScalarType::I8 => i8::MAX as f64,
+            ScalarType::U8 => u8::MAX as f64,
+            ScalarType::I16 => i16::MAX as f64,
+            ScalarType::U16 => u16::MAX as f64,
+            ScalarType::I32 => i32::MAX as f64,
+            ScalarType::U32 => u32::MAX as f64,
+            ScalarType::F32 | ScalarType::F64 => 1.0,
+        }
+    }
+}
+
+enum Property {
+    Scalar {
+        name: String,
+        ty: ScalarType,
+    },
+    List {
+        name: String,
+        count_ty: ScalarType,
+        item_ty: ScalarType,
+    },
+}
+
+impl Property {
+    fn name(&self) -> &str {
+        match self {
+            Property::Scalar { name, .. } | Property::List { name, .. } => name,
+        }
+    }
+}
+
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+struct Header {
+    format: Format,
+    elements: Vec<Element>,
+    lines: usize,
+}
+
+/// Loads ASCII or binary (either endianness) `.ply` files. Vertex positions,
+/// normals (`nx`, `ny`, `nz`), texture coordinates (`u`/`v`, `s`/`t` or
+/// `texture_u`/`texture_v`) and colors (`red`, `green`, `blue`) are read, as
+/// well as the faces, with polygons split into triangles. Colors of any type
+/// are taken as gamma corrected and converted to linear. Use a `VertexColor`
+/// texture to render them.
+pub fn load_ply(path: &dyn AsRef<Path>) -> Result<MeshData, LoadError> {
+    let path = path.as_ref();
+    let file = File::open(path).map_err(|err| LoadError::io(path, err))?;
+
+    read_ply(BufReader::new(file), path)
+}
+
+/// Parses `.ply` data, `path` is only used for error messages.
+pub fn read_ply<R: BufRead>(mut reader: R, path: &Path) -> Result<MeshData, LoadError> {
+    let header = read_header(&mut reader, path)?;
+
+    match header.format {
+        Format::Ascii => {
+            let mut source = AsciiSource {
+                lines: reader.lines(),
+                tokens: Vec::new(),
+                line_number: header.lines,
+                path,
+            };
+            read_elements(&mut source, &header, path)
+        }
+        Format::BinaryLittleEndian | Format::BinaryBigEndian => {
+            let mut source = BinarySource {
+                reader,
+                big_endian: header.format == Format::BinaryBigEndian,
+                path,
+            };
+            read_elements(&mut source, &header, path)
+        }
+    }
+}
+
+fn read_header<R: BufRead>(reader: &mut R, path: &Path) -> Result<Header, LoadError> {
+    let mut format = None;
+    let mut elements: Vec<Element> = Vec::new();
+    let mut line_number = 0;
+
+    loop {
+        let mut line = String::new();
+        let read = reader
+            .read_line(&mut line)
+            .map_err(|err| LoadError::io(path, err))?;
+        line_number += 1;
+        let error = |message: &str| LoadError::parse(path, line_number, message);
+
+        if read == 0 {
+            return Err(error("missing end_header"));
+        }
+
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        if line_number == 1 {
+            if tokens != ["ply"] {
+                return Err(error("not a PLY file"));
+            }
+            continue;
+        }
+
+        match tokens.as_slice() {
+            ["format", name, _version] => {
+                format = Some(match *name {
+                    "ascii" => Format::Ascii,
+                    "binary_little_endian" => Format::BinaryLittleEndian,
+                    "binary_big_endian" => Format::BinaryBigEndian,
+                    _ => return Err(error("unknown format")),
+                });
+            }
+            ["element", name, count] => {
+                let count = count.parse().map_err(|_| error("invalid element count"))?;
+                elements.push(Element {
+                    name: name.to_string(),
+                    count,
+                    properties: Vec::new(),
+                });
+            }
+            ["property", "list", count_ty, item_ty, name] => {
+                let property = Property::List {
+                    name: name.to_string(),
+                    count_ty: ScalarType::parse(count_ty)
+                        .ok_or_else(|| error("unknown property type"))?,
+                    item_ty: ScalarType::parse(item_ty)
+                        .ok_or_else(|| error("unknown property type"))?,
+                };
+                elements
+                    .last_mut()
+                    .ok_or_else(|| error("property before any element"))?
+                    .properties
+                    .push(property);
+            }
+            ["property", ty, name] => {
+                let property = Property::Scalar {
+                    name: name.to_string(),
+                    ty: ScalarType::parse(ty).ok_or_else(|| error("unknown property type"))?,
+                };
+                elements
+                    .last_mut()
+                    .ok_or_else(|| error("property before any element"))?
+                    .properties
+                    .push(property);
+            }
+            ["end_header"] => break,
+            ["comment", ..] | ["obj_info", ..] | [] => {}
+            _ => return Err(error("invalid header line")),
+        }
+    }
+
+    Ok(Header {
+        format: format.ok_or_else(|| LoadError::parse(path, line_number, "missing format"))?,
+        elements,
+        lines: line_number,
+    })
+}
+
+fn read_elements<S: ValueSource>(
+    source: &mut S,
+    header: &Header,
+    path: &Path,
+) -> Result<MeshData, LoadError> {
+    let mut data = MeshData::default();
+
+    for element in header.elements.iter() {
+        match element.name.as_str() {
+            "vertex" => read_vertices(source, element, &mut data)?,
+            "face" => read_faces(source, element, &mut data)?,
+            _ => {
+                for _ in 0..element.count {
+                    for property in element.properties.iter() {
+                        read_property(source, property)?;
+                    }
+                }
+            }
+        }
+    }
+
+    let vertex_count = data.positions.len() as u32;
+    if let Some(index) = data
+        .indices
+        .iter()
+        .flatten()
+        .find(|&&index| index >= vertex_count)
+    {
+        return Err(LoadError::format(
+            path,
+            format!("vertex index {} out of range", index),
+        ));
+    }
+
+    Ok(data)
+}
+
+fn read_vertices<S: ValueSource>(
+    source: &mut S,
+    element: &Element,
+    data: &mut MeshData,
+) -> Result<(), LoadError> {
+    let find = |names: &[&str]| {
+        element
+            .properties
+            .iter()
+            .position(|property| names.contains(&property.name()))
+    };
+    let find_all = |names: [&[&str]; 3]| match (find(names[0]), find(names[1]), find(names[2])) {
+        (Some(a), Some(b), Some(c)) => Some([a, b, c]),
+        _ => None,
+    };
+
+    let position = find_all([&["x"], &["y"], &["z"]])
+        .ok_or_else(|| source.error("vertices without x, y and z"))?;
+    let normal = find_all([&["nx"], &["ny"], &["nz"]]);
+    let color = find_all([&["red"], &["green"], &["blue"]]);
+    let uv = match (
+        find(&["u", "s", "texture_u"]),
+        find(&["v", "t", "texture_v"]),
+    ) {
+        (Some(u), Some(v)) => Some([u, v]),
+        _ => None,
+    };
+    let color_scales = color.map(|channels| {
+        channels.map(|channel| match &element.properties[channel] {
+            Property::Scalar { ty, .. } => ty.color_scale(),
+            Property::List { .. } => 1.0,
+        })
+    });
+
+    let mut values = vec![0.0; element.properties.len()];
+    for _ in 0..element.count {
+        for (value, property) in values.iter_mut().zip(element.properties.iter()) {
+            *value = read_property(source, property)?
+                .first()
+                .copied()
+                .unwrap_or(0.0);
+        }
+        let vector =
+            |[x, y, z]: [usize; 3]| Vec3::new(values[x] as f32, values[y] as f32, values[z] as f32);
+
+        data.positions.push(vector(position));
+        if let Some(normal) = normal {
+            data.normals.push(vector(normal));
+        }
+        if let Some([u, v]) = uv {
+            data.uvs.push((values[u] as f32, values[v] as f32));
+        }
+        if let (Some([red, green, blue]), Some([red_scale, green_scale, blue_scale])) =
+            (color, color_scales)
+        {
+            // Negative channels of signed types are clamped to black
+            let linear =
+                |value: f64, scale: f64| srgb_to_linear((value / scale).clamp(0.0, 1.0) as f32);
+            data.colors.push(Color::new(
+                linear(values[red], red_scale),
+                linear(values[green], green_scale),
+                linear(values[blue], blue_scale),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn read_faces<S: ValueSource>(
+    source: &mut S,
+    element: &Element,
+    data: &mut MeshData,
+) -> Result<(), LoadError> {
+    let indices = element
+        .properties
+        .iter()
+        .position(|property| {
+            matches!(property, Property::List { name, .. }
+                if name == "vertex_indices" || name == "vertex_index")
+        })
+        .ok_or_else(|| source.error("faces without vertex_indices"))?;
+
+    for _ in 0..element.count {
+        let mut corners = Vec::new();
+        for (i, property) in element.properties.iter().enumerate() {
+            let values = read_property(source, property)?;
+            if i == indices {
+                corners = values;
+            }
+        }
+
+        if corners.len() < 3 {
+            return Err(source.error("face with less than 3 vertices"));
+        }
+        if corners.iter().any(|&index| index < 0.0) {
+            return Err(source.error("negative vertex index"));
+        }
+
+        // Fan triangulation
+        for i in 1..corners.len() - 1 {
+            data.indices
+                .push([corners[0] as u32, corners[i] as u32, corners[i + 1] as u32]);
+        }
+    }
+
+    Ok(())
+}
+
+fn read_property<S: ValueSource>(
+    source: &mut S,
+    property: &Property,
+) -> Result<Vec<f64>, LoadError> {
+    match property {
+        Property::Scalar { ty, .. } => Ok(vec![source.next_value(*ty)?]),
+        Property::List {
+            count_ty, item_ty, ..
+        } => {
+            let count = source.next_value(*count_ty)?;
+            if count < 0.0 {
+                return Err(source.error("negative list length"));
+            }
+
+            (0..count as usize)
+                .map(|_| source.next_value(*item_ty))
+                .collect()
+        }
+    }
+}
+
+trait ValueSource {
+    fn next_value(&mut self, ty: ScalarType) -> Result<f64, LoadError>;
+
+    fn error(&self, message: &str) -> LoadError;
+}
+
+struct AsciiSource<'a, R: BufRead> {
+    lines: Lines<R>,
+    tokens: Vec<String>,
+    line_number: usize,
+    path: &'a Path,
+}
+
+impl<'a, R: BufRead> ValueSource for AsciiSource<'a, R> {
+    fn next_value(&mut self, _ty: ScalarType) -> Result<f64, LoadError> {
+        while self.tokens.is_empty() {
+            let line = match self.lines.next() {
+                Some(line) => line.map_err(|err| LoadError::io(self.path, err))?,
+                None => return Err(self.error("unexpected end of file")),
+            };
+            self.line_number += 1;
+            // Reversed, so tokens can be popped in order
+            self.tokens = line.split_whitespace().rev().map(String::from).collect();
+        }
+
+        let token = self.tokens.pop().unwrap();
+        token
+            .parse()
+            .map_err(|_| self.error(&format!("invalid number '{}'", token)))
+    }
+
+    fn error(&self, message: &str) -> LoadError {
+        LoadError::parse(self.path, self.line_number, message)
+    }
+}
+
+struct BinarySource<'a, R: Read> {
+    reader: R,
+    big_endian: bool,
+    path: &'a Path,
+}
+
+impl<'a, R: Read> ValueSource for BinarySource<'a, R> {
+    fn next_value(&mut self, ty: ScalarType) -> Result<f64, LoadError> {
+        let mut bytes = [0u8; 8];
+        let bytes = &mut bytes[..ty.size()];
+        self.reader.read_exact(bytes).map_err(|err| {
+            if err.kind() == std::io::ErrorKind::UnexpectedEof {
+                self.error("unexpected end of file")
+            } else {
+                LoadError::io(self.path, err)
+            }
+        })?;
+        if !self.big_endian {
+            bytes.reverse();
+        }
+
+        // Bytes are big endian from here on
+        Ok(match ty {
+            ScalarType::I8 => i8::from_be_bytes([bytes[0]]) as f64,
+            ScalarType::U8 => bytes[0] as f64,
+            ScalarType::I16 => i16::from_be_bytes([bytes[0], bytes[1]]) as f64,
+            ScalarType::U16 => u16::from_be_bytes([bytes[0], bytes[1]]) as f64,
+            ScalarType::I32 => i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+            ScalarType::U32 => u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+            ScalarType::F32 => f32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+            ScalarType::F64 => f64::from_be_bytes([
+                bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7],
+            ]),
+        })
+    }
+
+    fn error(&self, message: &str) -> LoadError {
+        LoadError::format(self.path, message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vectors::Point3;
+
+    const HEADER: &str = "element vertex 4\n\
+        property float x\nproperty float y\nproperty float z\n\
+        property uchar red\nproperty uchar green\nproperty uchar blue\n\
+        element face 1\nproperty list uchar int vertex_indices\nend_header\n";
+
+    fn binary_square(big_endian: bool) -> Vec<u8> {
+        let format = if big_endian {
+            "binary_big_endian"
+        } else {
+            "binary_little_endian"
+        };
+        let mut bytes = format!("ply\nformat {} 1.0\n{}", format, HEADER).into_bytes();
+
+        let corners = [(0.0f32, 0.0f32), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)];
+        for (x, y) in corners.iter() {
+            for value in [*x, *y, 0.0].iter() {
+                if big_endian {
+                    bytes.extend_from_slice(&value.to_be_bytes());
+                } else {
+                    bytes.extend_from_slice(&value.to_le_bytes());
+                }
+            }
+            bytes.extend_from_slice(&[255, 0, 0]);
+        }
+        bytes.push(4);
+        for index in 0..4i32 {
+            if big_endian {
+                bytes.extend_from_slice(&index.to_be_bytes());
+            } else {
+                bytes.extend_from_slice(&index.to_le_bytes());
+            }
+        }
+
+        bytes
+    }
+
+    #[test]
+    fn test_ascii() {
+        let source = format!(
+            "ply\nformat ascii 1.0\ncomment test\n{}\
+             0 0 0 255 0 0\n1 0 0 255 0 0\n1 1 0 255 0 0\n0 1 0 255 0 0\n4 0 1 2 3\n",
+            HEADER
+        );
+        let data = read_ply(source.as_bytes(), Path::new("test.ply")).unwrap();
+
+        assert_eq!(data.positions.len(), 4);
+        assert_eq!(data.indices, vec![[0, 1, 2], [0, 2, 3]]);
+        assert_eq!(data.colors[0], Color::new(1.0, 0.0, 0.0));
+        assert!(data.normals.is_empty());
+    }
+
+    #[test]
+    fn test_signed_and_float_colors() {
+        let source = "ply\nformat ascii 1.0\nelement vertex 1\n\
+            property float x\nproperty float y\nproperty float z\n\
+            property char red\nproperty float green\nproperty short blue\n\
+            end_header\n0 0 0 127 0.5 -100\n";
+        let data = read_ply(source.as_bytes(), Path::new("test.ply")).unwrap();
+        let color = data.colors[0];
+
+        assert!((color.x() - 1.0).abs() < 1e-6);
+        assert!((color.y() - srgb_to_linear(0.5)).abs() < 1e-6);
+        assert_eq!(color.z(), 0.0);
+    }
+
+    #[test]
+    fn test_binary_both_endians() {
+        for big_endian in [false, true].iter() {
+            let bytes = binary_square(*big_endian);
+            let data = read_ply(bytes.as_slice(), Path::new("test.ply")).unwrap();
+
+            assert_eq!(data.positions[2], Point3::new(1.0, 1.0, 0.0));
+            assert_eq!(data.indices, vec![[0, 1, 2], [0, 2, 3]]);
+        }
+    }
+
+    #[test]
+    fn test_truncated_ascii_reports_line() {
+        let source = format!("ply\nformat ascii 1.0\n{}0 0 0 255 0 0\n", HEADER);
+        let error = read_ply(source.as_bytes(), Path::new("test.ply"))
+            .err()
+            .unwrap();
+
+        assert_eq!(error.to_string(), "test.ply:13: unexpected end of file");
+    }
+}
@@ -0,0 +1,413 @@
+use crate::{
+    materials::Material,
+    objects::mesh::{MeshData, TriangleMesh},
+    vectors::{Point3, Vec3},
+};
+use std::{collections::HashMap, fs::File, io::Read, path::Path, sync::Arc};
+
+use super::LoadError;
+
+const BINARY_HEADER_SIZE: usize = 84;
+const BINARY_FACET_SIZE: usize = 50;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StlOptions {
+    /// Ignore the stored facet normals (often missing or wrong) and orient
+    /// facets by their vertex order alone
+    pub recompute_normals: bool,
+    /// Merge vertices closer than `weld_tolerance` to one already kept and
+    /// smooth the normals across the merged facets, the tolerance must be
+    /// positive
+    pub weld_vertices: bool,
+    pub weld_tolerance: f32,
+}
+
+impl Default for StlOptions {
+    fn default() -> Self {
+        Self {
+            recompute_normals: false,
+            weld_vertices: false,
+            weld_tolerance: 1e-5,
+        }
+    }
+}
+
+struct Facet {
+    normal: Vec3<f32>,
+    vertices: [Point3<f32>; 3],
+}
+
+/// Loads an ASCII or binary `.stl` file as a single mesh.
+pub fn load_stl(
+    path: &dyn AsRef<Path>,
+    material: Arc<dyn Material>,
+    options: StlOptions,
+) -> Result<TriangleMesh, LoadError> {
+    let path = path.as_ref();
+    let mut bytes = Vec::new();
+    File::open(path)
+        .and_then(|mut file| file.read_to_end(&mut bytes))
+        .map_err(|err| LoadError::io(path, err))?;
+
+    Ok(TriangleMesh::new(
+        read_stl(&bytes, path, options)?,
+        material,
+    ))
+}
+
+/// Parses `.stl` data, `path` is only used for error messages.
+pub fn read_stl(bytes: &[u8], path: &Path, options: StlOptions) -> Result<MeshData, LoadError> {
+    if options.weld_vertices && (options.weld_tolerance <= 0.0 || options.weld_tolerance.is_nan()) {
+        return Err(LoadError::format(path, "weld tolerance must be positive"));
+    }
+
+    // Binary files may start with "solid" too, so trust the size first
+    let facets = if is_binary(bytes) {
+        read_binary(bytes, path)?
+    } else if bytes.starts_with(b"solid") {
+        // Binary exporters often start the header with "solid" too, so text
+        // that isn't ASCII STL is most likely a truncated binary file
+        let facets = match std::str::from_utf8(bytes) {
+            Ok(text) => read_ascii(text, path)?,
+            Err(_) => Vec::new(),
+        };
+        if facets.is_empty() {
+            return Err(LoadError::format(path, "truncated binary STL"));
+        }
+        facets
+    } else {
+        return Err(LoadError::format(path, "not an STL file"));
+    };
+
+    let mut data = MeshData::default();
+    // Welded vertices by grid cell of size `weld_tolerance`
+    let mut welded: HashMap<(i64, i64, i64), Vec<u32>> = HashMap::new();
+
+    for facet in facets {
+        let mut vertices = facet.vertices;
+        let winding_normal = (vertices[1] - vertices[0]).cross(&(vertices[2] - vertices[0]));
+        if !options.recompute_normals && winding_normal.dot(&facet.normal) < 0.0 {
+            // Make the vertex order agree with the stored normal
+            vertices.swap(1, 2);
+        }
+
+        let mut face = [0; 3];
+        for (index, vertex) in face.iter_mut().zip(vertices.iter()) {
+            *index = if options.weld_vertices {
+                let cell = grid_cell(vertex, options.weld_tolerance);
+                match find_close(
+                    &welded,
+                    &data.positions,
+                    vertex,
+                    cell,
+                    options.weld_tolerance,
+                ) {
+                    Some(index) => index,
+                    None => {
+                        data.positions.push(*vertex);
+                        let index = data.positions.len() as u32 - 1;
+                        welded.entry(cell).or_default().push(index);
+                        index
+                    }
+                }
+            } else {
+                data.positions.push(*vertex);
+                data.positions.len() as u32 - 1
+            };
+        }
+
+        // Welding can collapse small facets into lines
+        if face[0] != face[1] && face[1] != face[2] && face[0] != face[2] {
+            data.indices.push(face);
+        }
+    }
+
+    if options.weld_vertices {
+        data.compute_normals();
+    }
+
+    Ok(data)
+}
+
+fn facet_count(bytes: &[u8]) -> usize {
+    u32::from_le_bytes([bytes[80], bytes[81], bytes[82], bytes[83]]) as usize
+}
+
+fn is_binary(bytes: &[u8]) -> bool {
+    if bytes.len() < BINARY_HEADER_SIZE {
+        return false;
+    }
+
+    // Text read as a facet count is huge, so an ASCII file never fits it
+    !bytes.starts_with(b"solid")
+        || bytes.len() >= BINARY_HEADER_SIZE + facet_count(bytes) * BINARY_FACET_SIZE
+}
+
+fn read_binary(bytes: &[u8], path: &Path) -> Result<Vec<Facet>, LoadError> {
+    let end = BINARY_HEADER_SIZE + facet_count(bytes) * BINARY_FACET_SIZE;
+    if bytes.len() < end {
+        return Err(LoadError::format(path, "truncated binary STL"));
+    }
+    let body = &bytes[BINARY_HEADER_SIZE..end];
+
+    let float = |chunk: &[u8], index: usize| {
+        let start = index * 4;
+        f32::from_le_bytes([
+            chunk[start],
+            chunk[start + 1],
+            chunk[start + 2],
+            chunk[start + 3],
+        ])
+    };
+    let vector = |chunk: &[u8], index: usize| {
+        Vec3::new(
+            float(chunk, index),
+            float(chunk, index + 1),
+            float(chunk, index + 2),
+        )
+    };
+
+    // Normal, three vertices, then a 2 byte attribute count nobody uses
+    Ok(body
+        .chunks(BINARY_FACET_SIZE)
+        .map(|chunk| Facet {
+            normal: vector(chunk, 0),
+            vertices: [vector(chunk, 3), vector(chunk, 6), vector(chunk, 9)],
+        })
+        .collect())
+}
+
+fn read_ascii(text: &str, path: &Path) -> Result<Vec<Facet>, LoadError> {
+    let mut facets = Vec::new();
+    let mut normal = Vec3::default();
+    let mut vertices = Vec::new();
+
+    for (index, line) in text.lines().enumerate() {
+        let line_number = index + 1;
+        let error = |message: &str| LoadError::parse(path, line_number, message);
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+
+        let vector = |values: &[&str]| -> Result<Vec3<f32>, LoadError> {
+            let mut parsed = [0.0; 3];
+            if values.len() != 3 {
+                return Err(error("expected 3 numbers"));
+            }
+            for (value, token) in parsed.iter_mut().zip(values.iter()) {
+                *value = token
+                    .parse()
+                    .map_err(|_| error(&format!("invalid number '{}'", token)))?;
+            }
+            Ok(Vec3::new(parsed[0], parsed[1], parsed[2]))
+        };
+
+        match tokens.as_slice() {
+            ["facet", "normal", values @ ..] => {
+                normal = vector(values)?;
+                vertices.clear();
+            }
+            ["vertex", values @ ..] => vertices.push(vector(values)?),
+            ["endfacet"] => {
+                if vertices.len() != 3 {
+                    return Err(error("facet without exactly 3 vertices"));
+                }
+                facets.push(Facet {
+                    normal,
+                    vertices: [vertices[0], vertices[1], vertices[2]],
+                });
+            }
+            _ => {}
+        }
+    }
+
+    Ok(facets)
+}
+
+fn grid_cell(point: &Point3<f32>, tolerance: f32) -> (i64, i64, i64) {
+    let cell = |value: f32| (value / tolerance).floor() as i64;
+
+    (cell(point.x()), cell(point.y()), cell(point.z()))
+}
+
+// Closer vertices can only be in the same or a neighbouring cell
+fn find_close(
+    welded: &HashMap<(i64, i64, i64), Vec<u32>>,
+    positions: &[Point3<f32>],
+    point: &Point3<f32>,
+    (x, y, z): (i64, i64, i64),
+    tolerance: f32,
+) -> Option<u32> {
+    for dx in -1..=1 {
+        for dy in -1..=1 {
+            for dz in -1..=1 {
+                let close = welded.get(&(x + dx, y + dy, z + dz)).and_then(|indices| {
+                    indices
+                        .iter()
+                        .find(|&&index| (positions[index as usize] - *point).norm() < tolerance)
+                });
+                if let Some(&index) = close {
+                    return Some(index);
+                }
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Two facets of a unit square, the second one wound against its normal
+    const ASCII: &str = "solid square
+  facet normal 0 0 1
+    outer loop
+      vertex 0 0 0
+      vertex 1 0 0
+      vertex 1 1 0
+    endloop
+  endfacet
+  facet normal 0 0 1
+    outer loop
+      vertex 0 0 0
+      vertex 0 1 0
+      vertex 1 1 0
+    endloop
+  endfacet
+endsolid square
+";
+
+    fn binary(facets: &[[f32; 12]]) -> Vec<u8> {
+        let mut bytes = vec![0u8; 80];
+        bytes.extend_from_slice(&(facets.len() as u32).to_le_bytes());
+        for facet in facets {
+            for value in facet.iter() {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+            bytes.extend_from_slice(&[0, 0]);
+        }
+
+        bytes
+    }
+
+    fn face_normal(data: &MeshData, face: usize) -> Vec3<f32> {
+        let [a, b, c] = data.indices[face];
+        let [a, b, c] = [
+            data.positions[a as usize],
+            data.positions[b as usize],
+            data.positions[c as usize],
+        ];
+
+        (b - a).cross(&(c - a))
+    }
+
+    #[test]
+    fn test_ascii_winding_follows_stored_normals() {
+        let data = read_stl(
+            ASCII.as_bytes(),
+            Path::new("test.stl"),
+            StlOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(data.positions.len(), 6);
+        assert!(face_normal(&data, 0).z() > 0.0);
+        assert!(face_normal(&data, 1).z() > 0.0);
+    }
+
+    #[test]
+    fn test_recomputed_normals_keep_winding() {
+        let options = StlOptions {
+            recompute_normals: true,
+            ..StlOptions::default()
+        };
+        let data = read_stl(ASCII.as_bytes(), Path::new("test.stl"), options).unwrap();
+
+        assert!(face_normal(&data, 1).z() < 0.0);
+    }
+
+    #[test]
+    fn test_binary_welding() {
+        let bytes = binary(&[
+            [0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0],
+        ]);
+        let options = StlOptions {
+            weld_vertices: true,
+            ..StlOptions::default()
+        };
+        let data = read_stl(&bytes, Path::new("test.stl"), options).unwrap();
+
+        assert_eq!(data.positions.len(), 4);
+        assert_eq!(data.indices.len(), 2);
+        assert_eq!(data.normals[0], Vec3::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn test_ascii_errors_have_line_numbers() {
+        let source = "solid broken\nfacet normal 0 0 1\nouter loop\nvertex 0 0 x\n";
+        let error = read_stl(
+            source.as_bytes(),
+            Path::new("test.stl"),
+            StlOptions::default(),
+        )
+        .err()
+        .unwrap();
+
+        assert_eq!(error.to_string(), "test.stl:4: invalid number 'x'");
+    }
+
+    #[test]
+    fn test_truncated_binary() {
+        let mut bytes = binary(&[[0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0]]);
+        bytes.truncate(bytes.len() - 10);
+        let error = read_stl(&bytes, Path::new("test.stl"), StlOptions::default())
+            .err()
+            .unwrap();
+
+        assert_eq!(error.to_string(), "test.stl: truncated binary STL");
+    }
+
+    #[test]
+    fn test_invalid_weld_tolerance() {
+        let options = StlOptions {
+            weld_vertices: true,
+            weld_tolerance: 0.0,
+            ..StlOptions::default()
+        };
+
+        assert!(read_stl(ASCII.as_bytes(), Path::new("test.stl"), options).is_err());
+    }
+
+    #[test]
+    fn test_welding_across_cells() {
+        // Vertices on either side of a cell boundary, and far apart in one cell
+        let bytes = binary(&[
+            [0.0, 0.0, 1.0, 0.99, 0.0, 0.0, 2.0, 0.0, 0.0, 2.0, 1.0, 0.0],
+            [
+                0.0, 0.0, 1.0, 1.01, 0.0, 0.0, 1.5, 1.0, 0.0, 0.76, 0.49, 0.49,
+            ],
+        ]);
+        let options = StlOptions {
+            weld_vertices: true,
+            weld_tolerance: 0.5,
+            ..StlOptions::default()
+        };
+        let data = read_stl(&bytes, Path::new("test.stl"), options).unwrap();
+
+        assert_eq!(data.positions.len(), 5);
+        assert_eq!(data.indices[1][0], data.indices[0][0]);
+    }
+
+    #[test]
+    fn test_truncated_binary_with_solid_header() {
+        let mut bytes = binary(&[[0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0]]);
+        bytes[..5].copy_from_slice(b"solid");
+        bytes.truncate(bytes.len() - 10);
+        let error = read_stl(&bytes, Path::new("test.stl"), StlOptions::default())
+            .err()
+            .unwrap();
+
+        assert_eq!(error.to_string(), "test.stl: truncated binary STL");
+    }
+}
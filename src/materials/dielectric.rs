@@ -2,8 +2,8 @@ use crate::{
     hit::Hit,
     materials::Material,
     rays::{Color, Ray},
+    sampling::Sampler,
 };
-use rand::{thread_rng, Rng};
 
 pub struct Dielectric {
     refractive_index: f32,
@@ -24,7 +24,7 @@ impl Dielectric {
 }
 
 impl Material for Dielectric {
-    fn scatter(&self, ray: &Ray, hit: &Hit) -> Option<(Ray, Color)> {
+    fn scatter(&self, ray: &Ray, hit: &Hit, sampler: &mut dyn Sampler) -> Option<(Ray, Color)> {
         let attenuation = Color::new(1.0, 1.0, 1.0);
         let refraction_ratio = if hit.is_front_facing {
             1.0 / self.refractive_index
@@ -36,8 +36,7 @@ impl Material for Dielectric {
         let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
 
         let cannot_refract = refraction_ratio * sin_theta > 1.0;
-        let should_reflect =
-            Self::get_reflectance(cos_theta, refraction_ratio) > thread_rng().gen();
+        let should_reflect = Self::get_reflectance(cos_theta, refraction_ratio) > sampler.next_1d();
 
         let direction = if cannot_refract || should_reflect {
             unit_direction.reflect(&hit.normal)
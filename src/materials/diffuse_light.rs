@@ -0,0 +1,41 @@
+use std::sync::Arc;
+
+use crate::{
+    hit::Hit,
+    materials::Material,
+    rays::{Color, Ray},
+    sampling::Sampler,
+    textures::{solid_color::SolidColor, Texture},
+    vectors::Point3,
+};
+
+pub struct DiffuseLight {
+    emit: Arc<dyn Texture>,
+    intensity: f32,
+}
+
+impl DiffuseLight {
+    pub fn new(color: Color, intensity: f32) -> Self {
+        Self {
+            emit: Arc::<SolidColor>::new(color.into()),
+            intensity,
+        }
+    }
+
+    pub fn with_texture(texture: Arc<dyn Texture>, intensity: f32) -> Self {
+        Self {
+            emit: texture,
+            intensity,
+        }
+    }
+}
+
+impl Material for DiffuseLight {
+    fn emit(&self, u: f32, v: f32, p: &Point3<f32>) -> Color {
+        self.intensity * self.emit.value(u, v, p)
+    }
+
+    fn scatter(&self, _ray: &Ray, _hit: &Hit, _sampler: &mut dyn Sampler) -> Option<(Ray, Color)> {
+        None
+    }
+}
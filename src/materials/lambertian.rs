@@ -4,6 +4,7 @@ use crate::{
     materials::Hit,
     materials::Material,
     rays::{Color, Ray},
+    sampling::Sampler,
     textures::{solid_color::SolidColor, Texture},
     vectors::Vec3,
 };
@@ -25,9 +26,9 @@ impl Lambertian {
 }
 
 impl Material for Lambertian {
-    fn scatter(&self, ray: &Ray, hit: &Hit) -> Option<(Ray, Color)> {
+    fn scatter(&self, ray: &Ray, hit: &Hit, sampler: &mut dyn Sampler) -> Option<(Ray, Color)> {
         // Alternative diffusion with Vec3::random_in_hemisphere is a bit faster
-        let mut scatter_direction = hit.normal + Vec3::random_unit_vector();
+        let mut scatter_direction = hit.normal + Vec3::random_unit_vector(sampler);
         // Catch degenerate scatter direction (->0)
         if scatter_direction.is_near_zero() {
             scatter_direction = hit.normal;
@@ -35,6 +36,6 @@ impl Material for Lambertian {
 
         let scattered_ray = Ray::new(hit.point, scatter_direction, ray.time());
 
-        Some((scattered_ray, self.albedo.value(hit.u, hit.v, &hit.point)))
+        Some((scattered_ray, self.albedo.value_at(hit)))
     }
 }
@@ -1,6 +1,7 @@
 use crate::{
     materials::Hit,
     rays::{Color, Ray},
+    sampling::Sampler,
     vectors::Vec3,
 };
 
@@ -21,12 +22,12 @@ impl Metal {
 }
 
 impl Material for Metal {
-    fn scatter(&self, ray: &Ray, hit: &Hit) -> Option<(Ray, Color)> {
+    fn scatter(&self, ray: &Ray, hit: &Hit, sampler: &mut dyn Sampler) -> Option<(Ray, Color)> {
         let reflected = ray.direction().unit_vector().reflect(&hit.normal);
         let fuzz = if self.fuzz == 0.0 {
             Vec3::default()
         } else {
-            self.fuzz * Vec3::random_in_unit_sphere()
+            self.fuzz * Vec3::random_in_unit_sphere(sampler)
         };
         let scattered_ray = Ray::new(hit.point, reflected + fuzz, ray.time());
 
@@ -1,14 +1,17 @@
 pub mod dielectric;
+pub mod diffuse_light;
 pub mod lambertian;
 pub mod metal;
 
 use crate::rays::Ray;
+use crate::sampling::Sampler;
 use crate::{hit::Hit, rays::Color, vectors::Point3};
 
-pub trait Material {
-    fn emit(&self, _u: f32, _v: u32, _p: &Point3<f32>) -> Color {
-        Color::new(1.0, 1.0, 1.0)
+pub trait Material: Send + Sync {
+    /// Light given off by the surface, added on top of what it scatters
+    fn emit(&self, _u: f32, _v: f32, _p: &Point3<f32>) -> Color {
+        Color::default()
     }
 
-    fn scatter(&self, ray: &Ray, hit: &Hit) -> Option<(Ray, Color)>;
+    fn scatter(&self, ray: &Ray, hit: &Hit, sampler: &mut dyn Sampler) -> Option<(Ray, Color)>;
 }
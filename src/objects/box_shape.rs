@@ -0,0 +1,86 @@
+use super::rect::{XYRect, XZRect, YZRect};
+use crate::aabb::AAAB;
+use crate::{
+    hit::{Hit, HitList, Hittable},
+    materials::Material,
+    rays::Ray,
+    vectors::Point3,
+};
+use std::sync::Arc;
+
+/// Axis-aligned box made of six rectangles with outward facing normals.
+pub struct BoxShape {
+    min: Point3<f32>,
+    max: Point3<f32>,
+    sides: HitList,
+}
+
+impl BoxShape {
+    pub fn new(min: Point3<f32>, max: Point3<f32>, material: Arc<dyn Material>) -> Self {
+        let mut sides = HitList::new();
+        let (x0, y0, z0) = (min.x(), min.y(), min.z());
+        let (x1, y1, z1) = (max.x(), max.y(), max.z());
+
+        sides.add(Arc::new(XYRect::new(x0, x1, y0, y1, z1, material.clone())));
+        sides.add(Arc::new(XYRect {
+            flip_normal: true,
+            ..XYRect::new(x0, x1, y0, y1, z0, material.clone())
+        }));
+        sides.add(Arc::new(XZRect::new(x0, x1, z0, z1, y1, material.clone())));
+        sides.add(Arc::new(XZRect {
+            flip_normal: true,
+            ..XZRect::new(x0, x1, z0, z1, y0, material.clone())
+        }));
+        sides.add(Arc::new(YZRect::new(y0, y1, z0, z1, x1, material.clone())));
+        sides.add(Arc::new(YZRect {
+            flip_normal: true,
+            ..YZRect::new(y0, y1, z0, z1, x0, material)
+        }));
+
+        Self { min, max, sides }
+    }
+
+    pub fn min(&self) -> Point3<f32> {
+        self.min
+    }
+
+    pub fn max(&self) -> Point3<f32> {
+        self.max
+    }
+}
+
+impl Hittable for BoxShape {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit> {
+        self.sides.hit(ray, t_min, t_max)
+    }
+
+    fn get_b_box(&self, _time0: f32, _time1: f32) -> Option<AAAB> {
+        Some(AAAB::new(self.min, self.max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{materials::lambertian::Lambertian, rays::Color, vectors::Vec3};
+
+    #[test]
+    fn test_normals_face_outward() {
+        let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let cube = BoxShape::new(
+            Point3::new(-1.0, -1.0, -1.0),
+            Point3::new(1.0, 1.0, 1.0),
+            material,
+        );
+
+        // Entering through the min x side, leaving through the max x side
+        let ray = Ray::new(Point3::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), 0.0);
+        let enter = cube.hit(&ray, 0.001, f32::INFINITY).unwrap();
+        assert!(enter.is_front_facing);
+        assert_eq!(enter.t, 4.0);
+
+        let exit = cube.hit(&ray, enter.t + 0.001, f32::INFINITY).unwrap();
+        assert!(!exit.is_front_facing);
+        assert_eq!(exit.t, 6.0);
+    }
+}
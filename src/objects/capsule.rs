@@ -0,0 +1,160 @@
+use super::cylinder::angle_u;
+use crate::aabb::AAAB;
+use crate::{
+    hit::{Hit, Hittable},
+    materials::Material,
+    rays::Ray,
+    roots::solve_quadratic,
+    vectors::{onb::Onb, Point3, Vec3},
+};
+use std::sync::Arc;
+
+/// Cylinder from `start` to `end` closed by hemispheres, i.e. every point
+/// within `radius` of the segment. `u` goes around the axis and `v` along it,
+/// over the whole length including the rounded ends.
+pub struct Capsule {
+    start: Point3<f32>,
+    end: Point3<f32>,
+    radius: f32,
+    material: Arc<dyn Material>,
+    // Local frame with the axis along w
+    onb: Onb,
+    height: f32,
+}
+
+impl Capsule {
+    pub fn new(
+        start: Point3<f32>,
+        end: Point3<f32>,
+        radius: f32,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        let axis = end - start;
+        assert!(!axis.is_near_zero(), "Capsule needs distinct start and end");
+        assert!(radius > 0.0, "Capsule needs a positive radius");
+
+        Self {
+            start,
+            end,
+            radius,
+            material,
+            onb: Onb::from_w(&axis),
+            height: axis.norm(),
+        }
+    }
+
+    pub fn start(&self) -> Point3<f32> {
+        self.start
+    }
+
+    pub fn end(&self) -> Point3<f32> {
+        self.end
+    }
+
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+}
+
+impl Hittable for Capsule {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit> {
+        let o = self.onb.to_local(&(ray.origin() - self.start));
+        let d = self.onb.to_local(&ray.direction());
+        let r2 = self.radius * self.radius;
+
+        // Closest candidate as (t, local point, local normal)
+        let mut closest: Option<(f32, Point3<f32>, Vec3<f32>)> = None;
+        let mut t_max = t_max;
+
+        let a = d.x() * d.x() + d.y() * d.y();
+        let b = 2.0 * (o.x() * d.x() + o.y() * d.y());
+        let c = o.x() * o.x() + o.y() * o.y() - r2;
+        if a != 0.0 {
+            if let Some((t0, t1)) = solve_quadratic(a, b, c) {
+                for t in [t0, t1] {
+                    let p = o + t * d;
+                    if t < t_min || t > t_max || p.z() < 0.0 || p.z() > self.height {
+                        continue;
+                    }
+                    closest = Some((t, p, Vec3::new(p.x(), p.y(), 0.0) / self.radius));
+                    t_max = t;
+                    break;
+                }
+            }
+        }
+
+        // Each hemisphere only counts beyond its end of the segment
+        for (center_z, outside) in [(0.0, -1.0), (self.height, 1.0)] {
+            let oc = o - Vec3::new(0.0, 0.0, center_z);
+            let roots = solve_quadratic(d.norm_sqr(), 2.0 * oc.dot(&d), oc.norm_sqr() - r2);
+            if let Some((t0, t1)) = roots {
+                for t in [t0, t1] {
+                    let p = o + t * d;
+                    if t < t_min || t > t_max || (p.z() - center_z) * outside < 0.0 {
+                        continue;
+                    }
+                    closest = Some((t, p, (oc + t * d) / self.radius));
+                    t_max = t;
+                    break;
+                }
+            }
+        }
+
+        let (t, p, normal) = closest?;
+        let v = (p.z() + self.radius) / (self.height + 2.0 * self.radius);
+        Some(Hit::new(
+            ray.at(t),
+            t,
+            angle_u(&p),
+            v.clamp(0.0, 1.0),
+            self.material.clone(),
+            ray,
+            &self.onb.local(&normal),
+        ))
+    }
+
+    fn get_b_box(&self, _time0: f32, _time1: f32) -> Option<AAAB> {
+        let radius = Vec3::new(self.radius, self.radius, self.radius);
+
+        Some(AAAB::new_surrounding_box(
+            AAAB::new(self.start - radius, self.start + radius),
+            AAAB::new(self.end - radius, self.end + radius),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{materials::lambertian::Lambertian, rays::Color};
+
+    #[test]
+    fn test_capsule_hits() {
+        let capsule = Capsule::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(0.0, 0.0, 2.0),
+            0.5,
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        );
+
+        // Rounded end along the axis
+        let end = Ray::new(Point3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let hit = capsule.hit(&end, 0.001, f32::INFINITY).unwrap();
+        assert!((hit.t - 2.5).abs() < 1e-5);
+        assert!((hit.normal - Vec3::new(0.0, 0.0, 1.0)).norm() < 1e-5);
+        assert!((hit.v - 1.0).abs() < 1e-5);
+
+        // Side, then from inside through the far side
+        let side = Ray::new(Point3::new(0.0, 3.0, 1.0), Vec3::new(0.0, -1.0, 0.0), 0.0);
+        let hit = capsule.hit(&side, 0.001, f32::INFINITY).unwrap();
+        assert!((hit.t - 2.5).abs() < 1e-5);
+        let exit = capsule.hit(&side, hit.t + 0.001, f32::INFINITY).unwrap();
+        assert!((exit.t - 3.5).abs() < 1e-5);
+        assert!(!exit.is_front_facing);
+
+        // Passes the rounded corner, where a capped cylinder would be hit
+        let corner = Ray::new(Point3::new(0.0, 0.45, 5.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let hit = capsule.hit(&corner, 0.001, f32::INFINITY).unwrap();
+        assert!(hit.point.z() < 2.5 && hit.point.z() > 2.0);
+    }
+}
@@ -0,0 +1,174 @@
+use super::{cylinder::angle_u, disk::disk_b_box};
+use crate::aabb::AAAB;
+use crate::{
+    hit::{Hit, Hittable},
+    materials::Material,
+    rays::Ray,
+    roots::solve_quadratic,
+    vectors::{onb::Onb, Point3, Vec3},
+};
+use std::sync::Arc;
+
+/// Finite cone with a disk of `radius` at `base` and its tip at `apex`,
+/// capped at the base unless turned off with `set_capped`. On the side `u`
+/// goes around the axis and `v` from base to apex.
+pub struct Cone {
+    base: Point3<f32>,
+    apex: Point3<f32>,
+    radius: f32,
+    capped: bool,
+    material: Arc<dyn Material>,
+    // Local frame with the axis along w
+    onb: Onb,
+    height: f32,
+}
+
+impl Cone {
+    pub fn new(
+        base: Point3<f32>,
+        apex: Point3<f32>,
+        radius: f32,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        let axis = apex - base;
+        assert!(!axis.is_near_zero(), "Cone needs distinct base and apex");
+        assert!(radius > 0.0, "Cone needs a positive radius");
+
+        Self {
+            base,
+            apex,
+            radius,
+            capped: true,
+            material,
+            onb: Onb::from_w(&axis),
+            height: axis.norm(),
+        }
+    }
+
+    pub fn set_capped(&mut self, capped: bool) {
+        self.capped = capped;
+    }
+
+    pub fn base(&self) -> Point3<f32> {
+        self.base
+    }
+
+    pub fn apex(&self) -> Point3<f32> {
+        self.apex
+    }
+
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+}
+
+impl Hittable for Cone {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit> {
+        let o = self.onb.to_local(&(ray.origin() - self.base));
+        let d = self.onb.to_local(&ray.direction());
+
+        // Closest candidate as (t, local normal, u, v)
+        let mut closest: Option<(f32, Vec3<f32>, f32, f32)> = None;
+        let mut t_max = t_max;
+
+        // x² + y² = (k (h - z))², measured from the apex down
+        let k = self.radius / self.height;
+        let k2 = k * k;
+        let (oz, dz) = (self.height - o.z(), -d.z());
+        let a = d.x() * d.x() + d.y() * d.y() - k2 * dz * dz;
+        let b = 2.0 * (o.x() * d.x() + o.y() * d.y() - k2 * oz * dz);
+        let c = o.x() * o.x() + o.y() * o.y() - k2 * oz * oz;
+        if let Some((t0, t1)) = solve_quadratic(a, b, c) {
+            for t in [t0, t1] {
+                let p = o + t * d;
+                // The equation also describes the mirrored cone above the apex
+                if t < t_min || t > t_max || p.z() < 0.0 || p.z() > self.height {
+                    continue;
+                }
+                let normal = Vec3::new(p.x(), p.y(), k2 * (self.height - p.z()));
+                if normal.is_near_zero() {
+                    continue;
+                }
+                closest = Some((t, normal.unit_vector(), angle_u(&p), p.z() / self.height));
+                t_max = t;
+                break;
+            }
+        }
+
+        if self.capped && d.z() != 0.0 {
+            let t = -o.z() / d.z();
+            let p = o + t * d;
+            if t >= t_min
+                && t <= t_max
+                && p.x() * p.x() + p.y() * p.y() <= self.radius * self.radius
+            {
+                let u = 0.5 * (p.x() / self.radius + 1.0);
+                let v = 0.5 * (p.y() / self.radius + 1.0);
+                closest = Some((t, Vec3::new(0.0, 0.0, -1.0), u, v));
+            }
+        }
+
+        let (t, normal, u, v) = closest?;
+        Some(Hit::new(
+            ray.at(t),
+            t,
+            u,
+            v,
+            self.material.clone(),
+            ray,
+            &self.onb.local(&normal),
+        ))
+    }
+
+    fn get_b_box(&self, _time0: f32, _time1: f32) -> Option<AAAB> {
+        Some(AAAB::new_surrounding_box(
+            disk_b_box(&self.base, &self.onb.w, self.radius),
+            AAAB::new(self.apex, self.apex),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{materials::lambertian::Lambertian, rays::Color};
+
+    #[test]
+    fn test_cone_hits() {
+        // Unit radius, height 2, standing on the xz plane
+        let cone = Cone::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(0.0, 2.0, 0.0),
+            1.0,
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        );
+
+        // Halfway up the radius is 0.5
+        let side = Ray::new(Point3::new(5.0, 1.0, 0.0), Vec3::new(-1.0, 0.0, 0.0), 0.0);
+        let hit = cone.hit(&side, 0.001, f32::INFINITY).unwrap();
+        assert!((hit.t - 4.5).abs() < 1e-5);
+        assert!((hit.v - 0.5).abs() < 1e-5);
+        let expected = Vec3::new(2.0, 1.0, 0.0).unit_vector();
+        assert!((hit.normal - expected).norm() < 1e-5);
+
+        let cap = Ray::new(Point3::new(0.3, -3.0, 0.0), Vec3::new(0.0, 1.0, 0.0), 0.0);
+        let hit = cone.hit(&cap, 0.001, f32::INFINITY).unwrap();
+        assert!((hit.t - 3.0).abs() < 1e-5);
+        assert!((hit.normal - Vec3::new(0.0, -1.0, 0.0)).norm() < 1e-5);
+
+        // Would hit the mirrored cone above the apex
+        let above = Ray::new(Point3::new(5.0, 3.0, 0.0), Vec3::new(-1.0, 0.0, 0.0), 0.0);
+        assert!(cone.hit(&above, 0.001, f32::INFINITY).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "positive radius")]
+    fn test_zero_radius() {
+        Cone::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(0.0, 2.0, 0.0),
+            0.0,
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        );
+    }
+}
@@ -0,0 +1,367 @@
+use super::{box_shape::BoxShape, cylinder::Cylinder, sphere::Sphere, transformed::Transformed};
+use crate::aabb::AAAB;
+use crate::{
+    hit::{Hit, Hittable},
+    rays::Ray,
+    vectors::Point3,
+};
+use std::sync::Arc;
+
+// Step past a boundary before looking for the next one, relative to `t`
+const STEP_EPSILON: f32 = 1e-4;
+// Upper bound on the boundaries collected along a single ray
+const MAX_BOUNDARIES: usize = 64;
+
+/// Stretch of a ray inside a solid, from the hit where it enters to the hit
+/// where it leaves.
+pub struct Interval {
+    pub enter: Hit,
+    pub exit: Hit,
+}
+
+/// Closed objects with an inside, which can be combined with `Csg`.
+pub trait Solid: Hittable {
+    /// The intervals along the line of `ray` that overlap `[t_min, t_max]`,
+    /// sorted and disjoint. They are kept whole, so they can start before
+    /// `t_min` or end after `t_max`. The default walks the boundaries with
+    /// repeated `hit` calls, which works for any closed surface.
+    fn intervals(&self, ray: &Ray, t_min: f32, t_max: f32) -> Vec<Interval> {
+        walk_boundaries(self, ray, t_min, t_max)
+    }
+}
+
+fn walk_boundaries<H: Hittable + ?Sized>(
+    solid: &H,
+    ray: &Ray,
+    t_min: f32,
+    t_max: f32,
+) -> Vec<Interval> {
+    let mut intervals = Vec::new();
+    let mut enter: Option<Hit> = None;
+    // Starts from the far past, to know whether `t_min` is inside
+    let mut t = f32::NEG_INFINITY;
+
+    for _ in 0..MAX_BOUNDARIES {
+        let hit = match solid.hit(ray, t, f32::INFINITY) {
+            Some(hit) => hit,
+            None => break,
+        };
+        t = hit.t + STEP_EPSILON * hit.t.abs().max(1.0);
+
+        if hit.is_front_facing {
+            if hit.t > t_max {
+                break;
+            }
+            enter = Some(hit);
+        } else if let Some(enter) = enter.take() {
+            if hit.t >= t_min {
+                intervals.push(Interval { enter, exit: hit });
+            }
+        }
+    }
+
+    intervals
+}
+
+impl Solid for Sphere {}
+
+impl Solid for BoxShape {}
+
+/// Open tubes have no inside, so they never add an interval.
+impl Solid for Cylinder {
+    fn intervals(&self, ray: &Ray, t_min: f32, t_max: f32) -> Vec<Interval> {
+        if self.is_capped() {
+            walk_boundaries(self, ray, t_min, t_max)
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+impl<S: Solid> Solid for Transformed<S> {
+    // `t` is the same in object space
+    fn intervals(&self, ray: &Ray, t_min: f32, t_max: f32) -> Vec<Interval> {
+        self.object
+            .intervals(&self.to_object_ray(ray), t_min, t_max)
+            .into_iter()
+            .map(|interval| Interval {
+                enter: self.to_world_hit(interval.enter),
+                exit: self.to_world_hit(interval.exit),
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CsgOperation {
+    Union,
+    Intersection,
+    /// Left minus right
+    Difference,
+}
+
+impl CsgOperation {
+    fn contains(&self, in_left: bool, in_right: bool) -> bool {
+        match self {
+            CsgOperation::Union => in_left || in_right,
+            CsgOperation::Intersection => in_left && in_right,
+            CsgOperation::Difference => in_left && !in_right,
+        }
+    }
+}
+
+/// Boolean combination of two solids. Surfaces keep the material of the
+/// solid they come from, so a difference shows the right solid's material
+/// where it cut into the left one.
+pub struct Csg {
+    left: Arc<dyn Solid>,
+    right: Arc<dyn Solid>,
+    operation: CsgOperation,
+}
+
+impl Csg {
+    pub fn new(left: Arc<dyn Solid>, right: Arc<dyn Solid>, operation: CsgOperation) -> Self {
+        Self {
+            left,
+            right,
+            operation,
+        }
+    }
+
+    pub fn union(left: Arc<dyn Solid>, right: Arc<dyn Solid>) -> Self {
+        Self::new(left, right, CsgOperation::Union)
+    }
+
+    pub fn intersection(left: Arc<dyn Solid>, right: Arc<dyn Solid>) -> Self {
+        Self::new(left, right, CsgOperation::Intersection)
+    }
+
+    pub fn difference(left: Arc<dyn Solid>, right: Arc<dyn Solid>) -> Self {
+        Self::new(left, right, CsgOperation::Difference)
+    }
+
+    pub fn operation(&self) -> CsgOperation {
+        self.operation
+    }
+}
+
+impl Solid for Csg {
+    fn intervals(&self, ray: &Ray, t_min: f32, t_max: f32) -> Vec<Interval> {
+        // Boundaries of both solids as (hit, from the left, entering). Spans
+        // outside the range don't change which solid the range is inside.
+        let mut events = Vec::new();
+        for (intervals, is_left) in [
+            (self.left.intervals(ray, t_min, t_max), true),
+            (self.right.intervals(ray, t_min, t_max), false),
+        ] {
+            for interval in intervals {
+                events.push((interval.enter, is_left, true));
+                events.push((interval.exit, is_left, false));
+            }
+        }
+        events.sort_by(|a, b| a.0.t.total_cmp(&b.0.t));
+
+        let mut intervals = Vec::new();
+        let (mut in_left, mut in_right) = (false, false);
+        let mut enter: Option<Hit> = None;
+
+        for (mut hit, is_left, entering) in events {
+            let was_inside = self.operation.contains(in_left, in_right);
+            if is_left {
+                in_left = entering;
+            } else {
+                in_right = entering;
+            }
+            let is_inside = self.operation.contains(in_left, in_right);
+            if was_inside == is_inside {
+                continue;
+            }
+
+            // The right solid's surface faces the other way once subtracted,
+            // the normal already points against the ray so only the side flips
+            if !is_left && self.operation == CsgOperation::Difference {
+                hit.is_front_facing = !hit.is_front_facing;
+            }
+
+            if is_inside {
+                enter = Some(hit);
+            } else if let Some(enter) = enter.take() {
+                intervals.push(Interval { enter, exit: hit });
+            }
+        }
+
+        intervals
+    }
+}
+
+impl Hittable for Csg {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit> {
+        // Much cheaper than walking the boundaries of both solids
+        if let Some(b_box) = self.get_b_box(ray.time(), ray.time()) {
+            if !b_box.is_in(ray, t_min, t_max) {
+                return None;
+            }
+        }
+
+        self.intervals(ray, t_min, t_max)
+            .into_iter()
+            .flat_map(|interval| vec![interval.enter, interval.exit])
+            .find(|hit| hit.t >= t_min && hit.t <= t_max)
+    }
+
+    fn get_b_box(&self, time0: f32, time1: f32) -> Option<AAAB> {
+        let left = self.left.get_b_box(time0, time1);
+        let right = self.right.get_b_box(time0, time1);
+
+        match self.operation {
+            CsgOperation::Union => Some(AAAB::new_surrounding_box(left?, right?)),
+            CsgOperation::Intersection => {
+                let (left, right) = (left?, right?);
+                let min = Point3::new(
+                    left.min().x().max(right.min().x()),
+                    left.min().y().max(right.min().y()),
+                    left.min().z().max(right.min().z()),
+                );
+                let max = Point3::new(
+                    left.max().x().min(right.max().x()),
+                    left.max().y().min(right.max().y()),
+                    left.max().z().min(right.max().z()),
+                );
+                // Disjoint solids have nothing in common to bound
+                if min.x() > max.x() || min.y() > max.y() || min.z() > max.z() {
+                    None
+                } else {
+                    Some(AAAB::new(min, max))
+                }
+            }
+            CsgOperation::Difference => left,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{materials::lambertian::Lambertian, rays::Color, vectors::Vec3};
+
+    fn sphere(x: f32) -> Arc<dyn Solid> {
+        Arc::new(Sphere {
+            center: Point3::new(x, 0.0, 0.0),
+            radius: 1.0,
+            material: Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        })
+    }
+
+    fn boundaries(solid: &dyn Solid, ray: &Ray) -> Vec<(f32, bool)> {
+        solid
+            .intervals(ray, f32::NEG_INFINITY, f32::INFINITY)
+            .into_iter()
+            .flat_map(|interval| vec![interval.enter, interval.exit])
+            .map(|hit| ((hit.t * 1e3).round() / 1e3, hit.is_front_facing))
+            .collect()
+    }
+
+    // Along the x axis, spheres at 0 and 1 overlap between 0 and 1
+    fn ray() -> Ray {
+        Ray::new(Point3::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), 0.0)
+    }
+
+    #[test]
+    fn test_operations() {
+        let union = Csg::union(sphere(0.0), sphere(1.0));
+        assert_eq!(boundaries(&union, &ray()), vec![(4.0, true), (7.0, false)]);
+
+        let intersection = Csg::intersection(sphere(0.0), sphere(1.0));
+        assert_eq!(
+            boundaries(&intersection, &ray()),
+            vec![(5.0, true), (6.0, false)]
+        );
+
+        // The cut surface at t = 5 comes from inside the right sphere
+        let difference = Csg::difference(sphere(0.0), sphere(1.0));
+        assert_eq!(
+            boundaries(&difference, &ray()),
+            vec![(4.0, true), (5.0, false)]
+        );
+        let reverse = Csg::difference(sphere(1.0), sphere(0.0));
+        assert_eq!(
+            boundaries(&reverse, &ray()),
+            vec![(6.0, true), (7.0, false)]
+        );
+    }
+
+    #[test]
+    fn test_hit_from_inside() {
+        // Starting inside the lens, the first hit is where it is left
+        let intersection = Csg::intersection(sphere(0.0), sphere(1.0));
+        let ray = Ray::new(Point3::new(0.5, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), 0.0);
+        let hit = intersection.hit(&ray, 0.001, f32::INFINITY).unwrap();
+
+        assert!((hit.t - 0.5).abs() < 1e-4);
+        assert!(!hit.is_front_facing);
+        assert_eq!(hit.normal, Vec3::new(-1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_nested_with_box_and_cylinder() {
+        let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let cube: Arc<dyn Solid> = Arc::new(BoxShape::new(
+            Point3::new(-1.0, -1.0, -1.0),
+            Point3::new(1.0, 1.0, 1.0),
+            material.clone(),
+        ));
+        // Drilled along x, then the drill hole is filled with a sphere
+        let drill: Arc<dyn Solid> = Arc::new(Cylinder::new(
+            Point3::new(-2.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 0.0),
+            0.5,
+            material,
+        ));
+        let drilled: Arc<dyn Solid> = Arc::new(Csg::difference(cube, drill));
+        assert!(boundaries(drilled.as_ref(), &ray()).is_empty());
+
+        let filled = Csg::union(drilled, sphere(0.0));
+        assert_eq!(boundaries(&filled, &ray()), vec![(4.0, true), (6.0, false)]);
+    }
+
+    #[test]
+    fn test_open_tube_has_no_inside() {
+        let mut tube = Cylinder::new(
+            Point3::new(-2.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 0.0),
+            0.5,
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        );
+        let across = Ray::new(Point3::new(0.0, -5.0, 0.0), Vec3::new(0.0, 1.0, 0.0), 0.0);
+        assert_eq!(boundaries(&tube, &across).len(), 2);
+
+        tube.set_capped(false);
+        assert!(boundaries(&tube, &across).is_empty());
+    }
+
+    #[test]
+    fn test_disjoint_intersection_b_box() {
+        let overlapping = Csg::intersection(sphere(0.0), sphere(1.0));
+        let b_box = overlapping.get_b_box(0.0, 1.0).unwrap();
+        assert_eq!(b_box.min().x(), 0.0);
+        assert_eq!(b_box.max().x(), 1.0);
+
+        let disjoint = Csg::intersection(sphere(0.0), sphere(3.0));
+        assert!(disjoint.get_b_box(0.0, 1.0).is_none());
+    }
+
+    #[test]
+    fn test_intervals_outside_range_are_dropped() {
+        // Spheres at 0 and 4 are crossed for t in [4, 6] and [8, 10]
+        let union = Csg::union(sphere(0.0), sphere(4.0));
+        let boundaries: Vec<f32> = union
+            .intervals(&ray(), 7.0, 11.0)
+            .into_iter()
+            .flat_map(|interval| vec![interval.enter.t, interval.exit.t])
+            .collect();
+
+        assert_eq!(boundaries.len(), 2);
+        assert!((boundaries[0] - 8.0).abs() < 1e-4);
+        assert!(union.hit(&ray(), 0.001, 3.0).is_none());
+    }
+}
@@ -0,0 +1,194 @@
+use super::disk::disk_b_box;
+use crate::aabb::AAAB;
+use crate::{
+    hit::{Hit, Hittable},
+    materials::Material,
+    rays::Ray,
+    roots::solve_quadratic,
+    vectors::{onb::Onb, Point3, Vec3},
+};
+use std::{f32::consts::PI, sync::Arc};
+
+/// Finite cylinder from `base` to `top`, capped on both ends unless turned
+/// into an open tube with `set_capped`. On the side `u` goes around the axis
+/// and `v` from base to top, the caps are mapped to the unit square.
+pub struct Cylinder {
+    base: Point3<f32>,
+    top: Point3<f32>,
+    radius: f32,
+    capped: bool,
+    material: Arc<dyn Material>,
+    // Local frame with the axis along w
+    onb: Onb,
+    height: f32,
+}
+
+impl Cylinder {
+    pub fn new(
+        base: Point3<f32>,
+        top: Point3<f32>,
+        radius: f32,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        let axis = top - base;
+        assert!(!axis.is_near_zero(), "Cylinder needs distinct base and top");
+        assert!(radius > 0.0, "Cylinder needs a positive radius");
+
+        Self {
+            base,
+            top,
+            radius,
+            capped: true,
+            material,
+            onb: Onb::from_w(&axis),
+            height: axis.norm(),
+        }
+    }
+
+    pub fn set_capped(&mut self, capped: bool) {
+        self.capped = capped;
+    }
+
+    pub fn is_capped(&self) -> bool {
+        self.capped
+    }
+
+    pub fn base(&self) -> Point3<f32> {
+        self.base
+    }
+
+    pub fn top(&self) -> Point3<f32> {
+        self.top
+    }
+
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+}
+
+impl Hittable for Cylinder {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit> {
+        let o = self.onb.to_local(&(ray.origin() - self.base));
+        let d = self.onb.to_local(&ray.direction());
+
+        // Closest candidate as (t, local normal, u, v)
+        let mut closest: Option<(f32, Vec3<f32>, f32, f32)> = None;
+        let mut t_max = t_max;
+
+        let a = d.x() * d.x() + d.y() * d.y();
+        let b = 2.0 * (o.x() * d.x() + o.y() * d.y());
+        let c = o.x() * o.x() + o.y() * o.y() - self.radius * self.radius;
+        if a != 0.0 {
+            if let Some((t0, t1)) = solve_quadratic(a, b, c) {
+                for t in [t0, t1] {
+                    let p = o + t * d;
+                    if t < t_min || t > t_max || p.z() < 0.0 || p.z() > self.height {
+                        continue;
+                    }
+                    let normal = Vec3::new(p.x(), p.y(), 0.0) / self.radius;
+                    closest = Some((t, normal, angle_u(&p), p.z() / self.height));
+                    t_max = t;
+                    break;
+                }
+            }
+        }
+
+        if self.capped && d.z() != 0.0 {
+            for (z, normal_z) in [(0.0, -1.0), (self.height, 1.0)] {
+                let t = (z - o.z()) / d.z();
+                let p = o + t * d;
+                if t < t_min
+                    || t > t_max
+                    || p.x() * p.x() + p.y() * p.y() > self.radius * self.radius
+                {
+                    continue;
+                }
+                let u = 0.5 * (p.x() / self.radius + 1.0);
+                let v = 0.5 * (p.y() / self.radius + 1.0);
+                closest = Some((t, Vec3::new(0.0, 0.0, normal_z), u, v));
+                t_max = t;
+            }
+        }
+
+        let (t, normal, u, v) = closest?;
+        Some(Hit::new(
+            ray.at(t),
+            t,
+            u,
+            v,
+            self.material.clone(),
+            ray,
+            &self.onb.local(&normal),
+        ))
+    }
+
+    fn get_b_box(&self, _time0: f32, _time1: f32) -> Option<AAAB> {
+        Some(AAAB::new_surrounding_box(
+            disk_b_box(&self.base, &self.onb.w, self.radius),
+            disk_b_box(&self.top, &self.onb.w, self.radius),
+        ))
+    }
+}
+
+/// Angle around the local z axis, mapped to [0, 1].
+pub(crate) fn angle_u(p: &Point3<f32>) -> f32 {
+    (p.y().atan2(p.x()) + PI) / (2.0 * PI)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{materials::lambertian::Lambertian, rays::Color};
+
+    fn cylinder() -> Cylinder {
+        // Lying along x, from 0 to 4
+        Cylinder::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(4.0, 0.0, 0.0),
+            1.0,
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        )
+    }
+
+    #[test]
+    fn test_side_and_cap_hits() {
+        let mut cylinder = cylinder();
+
+        let side = Ray::new(Point3::new(1.0, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0), 0.0);
+        let hit = cylinder.hit(&side, 0.001, f32::INFINITY).unwrap();
+        assert!((hit.t - 4.0).abs() < 1e-5);
+        assert!((hit.normal - Vec3::new(0.0, 1.0, 0.0)).norm() < 1e-5);
+        assert!((hit.v - 0.25).abs() < 1e-5);
+
+        let cap = Ray::new(Point3::new(8.0, 0.5, 0.0), Vec3::new(-1.0, 0.0, 0.0), 0.0);
+        let hit = cylinder.hit(&cap, 0.001, f32::INFINITY).unwrap();
+        assert!((hit.t - 4.0).abs() < 1e-5);
+        assert!((hit.normal - Vec3::new(1.0, 0.0, 0.0)).norm() < 1e-5);
+        assert!(hit.is_front_facing);
+
+        // Through the open end, out the inside of the side
+        cylinder.set_capped(false);
+        let ray = Ray::new(Point3::new(5.0, 0.0, 0.0), Vec3::new(-1.0, 0.3, 0.0), 0.0);
+        let hit = cylinder.hit(&ray, 0.001, f32::INFINITY).unwrap();
+        assert!(!hit.is_front_facing);
+    }
+
+    #[test]
+    fn test_b_box() {
+        let b_box = cylinder().get_b_box(0.0, 0.0).unwrap();
+
+        assert!((b_box.min() - Point3::new(0.0, -1.0, -1.0)).norm() < 1e-5);
+        assert!((b_box.max() - Point3::new(4.0, 1.0, 1.0)).norm() < 1e-5);
+    }
+
+    #[test]
+    #[should_panic(expected = "positive radius")]
+    fn test_zero_radius() {
+        Cylinder::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(4.0, 0.0, 0.0),
+            0.0,
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        );
+    }
+}
@@ -0,0 +1,233 @@
+use crate::aabb::{AAAB, B_BOX_PADDING};
+use crate::{
+    hit::{Hit, Hittable},
+    materials::Material,
+    rays::Ray,
+    vectors::{Point3, Vec3},
+};
+use std::{f32::consts::PI, sync::Arc};
+
+use super::PARALLEL_EPSILON;
+
+/// Flat disk facing `normal`. The hit's `u` is the angle around the center
+/// and `v` the distance from it, both in [0, 1].
+pub struct Disk {
+    ring: Ring,
+}
+
+/// Disk with a hole of `inner_radius` in the middle, `v` goes from the inner
+/// to the outer edge.
+pub struct Annulus {
+    ring: Ring,
+}
+
+impl Disk {
+    pub fn new(
+        center: Point3<f32>,
+        normal: Vec3<f32>,
+        radius: f32,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        assert!(radius > 0.0, "Disk needs a positive radius");
+
+        Self {
+            ring: Ring::new(center, normal, 0.0, radius, material),
+        }
+    }
+
+    pub fn center(&self) -> Point3<f32> {
+        self.ring.center
+    }
+
+    pub fn normal(&self) -> Vec3<f32> {
+        self.ring.normal
+    }
+
+    pub fn radius(&self) -> f32 {
+        self.ring.outer_radius
+    }
+}
+
+impl Annulus {
+    pub fn new(
+        center: Point3<f32>,
+        normal: Vec3<f32>,
+        inner_radius: f32,
+        outer_radius: f32,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        assert!(
+            0.0 <= inner_radius && inner_radius < outer_radius,
+            "Annulus needs 0 <= inner_radius < outer_radius"
+        );
+
+        Self {
+            ring: Ring::new(center, normal, inner_radius, outer_radius, material),
+        }
+    }
+
+    pub fn center(&self) -> Point3<f32> {
+        self.ring.center
+    }
+
+    pub fn normal(&self) -> Vec3<f32> {
+        self.ring.normal
+    }
+
+    pub fn inner_radius(&self) -> f32 {
+        self.ring.inner_radius
+    }
+
+    pub fn outer_radius(&self) -> f32 {
+        self.ring.outer_radius
+    }
+}
+
+impl Hittable for Disk {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit> {
+        self.ring.hit(ray, t_min, t_max)
+    }
+
+    fn get_b_box(&self, _time0: f32, _time1: f32) -> Option<AAAB> {
+        Some(self.ring.get_b_box())
+    }
+}
+
+impl Hittable for Annulus {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit> {
+        self.ring.hit(ray, t_min, t_max)
+    }
+
+    fn get_b_box(&self, _time0: f32, _time1: f32) -> Option<AAAB> {
+        Some(self.ring.get_b_box())
+    }
+}
+
+// A disk is a ring without a hole
+struct Ring {
+    center: Point3<f32>,
+    normal: Vec3<f32>,
+    // In-plane axes the angle is measured from
+    tangent: Vec3<f32>,
+    bitangent: Vec3<f32>,
+    inner_radius: f32,
+    outer_radius: f32,
+    material: Arc<dyn Material>,
+}
+
+impl Ring {
+    fn new(
+        center: Point3<f32>,
+        normal: Vec3<f32>,
+        inner_radius: f32,
+        outer_radius: f32,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        let normal = normal.unit_vector();
+        let (tangent, bitangent) = normal.orthonormal_basis();
+
+        Self {
+            center,
+            normal,
+            tangent,
+            bitangent,
+            inner_radius,
+            outer_radius,
+            material,
+        }
+    }
+
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit> {
+        let denominator = self.normal.dot(&ray.direction());
+        if denominator.abs() < PARALLEL_EPSILON {
+            return None;
+        }
+
+        let t = (self.center - ray.origin()).dot(&self.normal) / denominator;
+        if t < t_min || t > t_max {
+            return None;
+        }
+
+        let point = ray.at(t);
+        let offset = point - self.center;
+        let distance_sqr = offset.norm_sqr();
+        if distance_sqr > self.outer_radius * self.outer_radius
+            || distance_sqr < self.inner_radius * self.inner_radius
+        {
+            return None;
+        }
+
+        let phi = offset.dot(&self.bitangent).atan2(offset.dot(&self.tangent)) + PI;
+        let u = phi / (2.0 * PI);
+        let v = (distance_sqr.sqrt() - self.inner_radius) / (self.outer_radius - self.inner_radius);
+
+        Some(Hit::new(
+            point,
+            t,
+            u,
+            v,
+            self.material.clone(),
+            ray,
+            &self.normal,
+        ))
+    }
+
+    fn get_b_box(&self) -> AAAB {
+        disk_b_box(&self.center, &self.normal, self.outer_radius).pad(B_BOX_PADDING)
+    }
+}
+
+/// Tight box of a disk, `normal` has to be a unit vector.
+pub fn disk_b_box(center: &Point3<f32>, normal: &Vec3<f32>, radius: f32) -> AAAB {
+    // Along every axis the disk reaches radius * sin of the angle to the normal
+    let extent = |n: f32| radius * (1.0 - n * n).max(0.0).sqrt();
+    let half = Vec3::new(extent(normal.x()), extent(normal.y()), extent(normal.z()));
+
+    AAAB::new(*center - half, *center + half)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{materials::lambertian::Lambertian, rays::Color};
+
+    #[test]
+    fn test_annulus_hole() {
+        let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let annulus = Annulus::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            1.0,
+            2.0,
+            material,
+        );
+        let down = Vec3::new(0.0, -1.0, 0.0);
+
+        let center = Ray::new(Point3::new(0.0, 3.0, 0.0), down, 0.0);
+        assert!(annulus.hit(&center, 0.001, f32::INFINITY).is_none());
+
+        let ring = Ray::new(Point3::new(1.5, 3.0, 0.0), down, 0.0);
+        let hit = annulus.hit(&ring, 0.001, f32::INFINITY).unwrap();
+        assert!((hit.v - 0.5).abs() < 1e-6);
+
+        let outside = Ray::new(Point3::new(2.5, 3.0, 0.0), down, 0.0);
+        assert!(annulus.hit(&outside, 0.001, f32::INFINITY).is_none());
+    }
+
+    #[test]
+    fn test_tilted_disk_b_box() {
+        let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let disk = Disk::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 1.0, 0.0),
+            1.0,
+            material,
+        );
+        let b_box = disk.get_b_box(0.0, 0.0).unwrap();
+
+        let diagonal = 0.5f32.sqrt();
+        assert!((b_box.max().x() - diagonal).abs() < 1e-4);
+        assert!((b_box.max().y() - diagonal).abs() < 1e-4);
+        assert!((b_box.max().z() - 1.0).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,165 @@
+use super::transformed::Transformed;
+use crate::{
+    aabb::AAAB,
+    bvh::BVHNode,
+    hit::{Hit, HitList, Hittable},
+    materials::Material,
+    rays::Ray,
+    vectors::transform::Transform,
+};
+use std::sync::Arc;
+
+/// Shared geometry for instances, with its own BVH over `objects`.
+pub fn prototype(objects: HitList, time0: f32, time1: f32) -> Arc<dyn Hittable> {
+    assert!(!objects.is_empty(), "A prototype needs at least one object");
+
+    if objects.len() == 1 {
+        return objects.get(0).unwrap().clone();
+    }
+
+    Arc::new(BVHNode::new(&objects, time0, time1))
+}
+
+/// One placement of a shared prototype. Instances only hold a reference to
+/// the geometry, so thousands of them cost little more than their transforms.
+pub struct Instance {
+    object: Transformed<Arc<dyn Hittable>>,
+    material: Option<Arc<dyn Material>>,
+}
+
+impl Instance {
+    pub fn new(prototype: Arc<dyn Hittable>, transform: Transform) -> Self {
+        Self {
+            object: Transformed::new(prototype, transform),
+            material: None,
+        }
+    }
+
+    /// Replaces the prototype's materials for this instance only.
+    pub fn set_material(&mut self, material: Arc<dyn Material>) {
+        self.material = Some(material);
+    }
+
+    pub fn prototype(&self) -> &Arc<dyn Hittable> {
+        &self.object.object
+    }
+
+    pub fn transform(&self) -> &Transform {
+        &self.object.transform
+    }
+}
+
+impl Hittable for Instance {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit> {
+        let mut hit = self.object.hit(ray, t_min, t_max)?;
+        if let Some(material) = &self.material {
+            hit.material = material.clone();
+        }
+
+        Some(hit)
+    }
+
+    fn get_b_box(&self, time0: f32, time1: f32) -> Option<AAAB> {
+        self.object.get_b_box(time0, time1)
+    }
+}
+
+/// Two-level acceleration structure: a top level BVH over the instances'
+/// world space boxes, while every prototype keeps its own bottom level BVH
+/// in object space.
+pub struct InstanceTree {
+    root: BVHNode,
+    len: usize,
+}
+
+impl InstanceTree {
+    pub fn new(instances: Vec<Instance>, time0: f32, time1: f32) -> Self {
+        assert!(!instances.is_empty(), "An instance tree needs instances");
+
+        let len = instances.len();
+        let mut list = HitList::new();
+        for instance in instances {
+            list.add(Arc::new(instance));
+        }
+
+        Self {
+            root: BVHNode::new(&list, time0, time1),
+            len,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl Hittable for InstanceTree {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit> {
+        self.root.hit(ray, t_min, t_max)
+    }
+
+    fn get_b_box(&self, time0: f32, time1: f32) -> Option<AAAB> {
+        self.root.get_b_box(time0, time1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        materials::{lambertian::Lambertian, metal::Metal},
+        objects::sphere::Sphere,
+        rays::Color,
+        vectors::{Point3, Vec3},
+    };
+
+    fn sphere_prototype() -> Arc<dyn Hittable> {
+        let mut objects = HitList::new();
+        objects.add(Arc::new(Sphere {
+            center: Point3::new(0.0, 0.0, 0.0),
+            radius: 0.5,
+            material: Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        }));
+        objects.add(Arc::new(Sphere {
+            center: Point3::new(0.0, 1.0, 0.0),
+            radius: 0.25,
+            material: Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        }));
+
+        prototype(objects, 0.0, 1.0)
+    }
+
+    #[test]
+    fn test_instances_share_the_prototype() {
+        let shared = sphere_prototype();
+        let instances = (0..100)
+            .map(|i| {
+                let offset = Vec3::new((i % 10) as f32 * 2.0, 0.0, (i / 10) as f32 * -2.0);
+                Instance::new(shared.clone(), Transform::translation(offset))
+            })
+            .collect();
+        let tree = InstanceTree::new(instances, 0.0, 1.0);
+
+        assert_eq!(tree.len(), 100);
+        assert_eq!(Arc::strong_count(&shared), 101);
+
+        let ray = Ray::new(Point3::new(6.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let hit = tree.hit(&ray, 0.001, f32::INFINITY).unwrap();
+        assert!((hit.point - Point3::new(6.0, 0.0, 0.5)).norm() < 1e-4);
+    }
+
+    #[test]
+    fn test_material_override() {
+        let mut instance = Instance::new(sphere_prototype(), Transform::identity());
+        let metal: Arc<dyn Material> = Arc::new(Metal::new(Color::new(0.9, 0.9, 0.9), 0.0));
+        instance.set_material(metal.clone());
+
+        let ray = Ray::new(Point3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let hit = instance.hit(&ray, 0.001, f32::INFINITY).unwrap();
+        assert!(Arc::ptr_eq(&hit.material, &metal));
+    }
+}
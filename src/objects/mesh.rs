@@ -0,0 +1,359 @@
+use crate::aabb::{AAAB, B_BOX_PADDING};
+use crate::{
+    hit::{Hit, Hittable},
+    materials::Material,
+    rays::{Color, Ray},
+    vectors::{Point3, Vec3},
+};
+use std::sync::Arc;
+
+use super::triangle::intersect_triangle;
+
+const MAX_FACES_PER_LEAF: usize = 4;
+const MAX_DEPTH: usize = 64;
+
+/// Indexed triangle geometry. `normals`, `uvs` and `colors` are either empty
+/// or hold one entry per position, `indices` holds three positions per face.
+#[derive(Clone, Default)]
+pub struct MeshData {
+    pub positions: Vec<Point3<f32>>,
+    pub normals: Vec<Vec3<f32>>,
+    pub uvs: Vec<(f32, f32)>,
+    pub colors: Vec<Color>,
+    pub indices: Vec<[u32; 3]>,
+}
+
+impl MeshData {
+    /// Replaces the normals with smooth ones, averaged from the faces around
+    /// each vertex (weighted by face area).
+    pub fn compute_normals(&mut self) {
+        let mut normals = vec![Vec3::default(); self.positions.len()];
+
+        for face in self.indices.iter() {
+            let [p0, p1, p2] = self.face_positions(face);
+            // Cross product length is twice the face area
+            let normal = (p1 - p0).cross(&(p2 - p0));
+            for &index in face.iter() {
+                normals[index as usize] += normal;
+            }
+        }
+
+        self.normals = normals
+            .into_iter()
+            .map(|normal| {
+                if normal.is_near_zero() {
+                    normal
+                } else {
+                    normal.unit_vector()
+                }
+            })
+            .collect();
+    }
+
+    fn face_positions(&self, face: &[u32; 3]) -> [Point3<f32>; 3] {
+        [
+            self.positions[face[0] as usize],
+            self.positions[face[1] as usize],
+            self.positions[face[2] as usize],
+        ]
+    }
+}
+
+// Interior nodes have `count == 0` and `offset` pointing to their second
+// child, the first child directly follows the node. Leaves cover
+// `faces[offset..offset + count]`.
+struct MeshNode {
+    b_box: AAAB,
+    offset: u32,
+    count: u32,
+}
+
+/// Triangle mesh sharing its vertex buffers between all faces, with its own
+/// bounding volume hierarchy over the faces. The whole mesh is a single
+/// `Hittable`, so it goes into a `HitList` or `BVHNode` as one object.
+pub struct TriangleMesh {
+    data: Arc<MeshData>,
+    material: Arc<dyn Material>,
+    nodes: Vec<MeshNode>,
+    faces: Vec<u32>,
+}
+
+impl TriangleMesh {
+    pub fn new(data: MeshData, material: Arc<dyn Material>) -> Self {
+        Self::from_shared(Arc::new(data), material)
+    }
+
+    /// Builds the mesh over data shared with others, like a `VertexColor`
+    /// texture.
+    pub fn from_shared(data: Arc<MeshData>, material: Arc<dyn Material>) -> Self {
+        let vertex_count = data.positions.len();
+        assert!(data.normals.is_empty() || data.normals.len() == vertex_count);
+        assert!(data.uvs.is_empty() || data.uvs.len() == vertex_count);
+        assert!(data.colors.is_empty() || data.colors.len() == vertex_count);
+        assert!(data
+            .indices
+            .iter()
+            .flatten()
+            .all(|&index| (index as usize) < vertex_count));
+
+        let face_boxes: Vec<AAAB> = data
+            .indices
+            .iter()
+            .map(|face| AAAB::surrounding_points(&data.face_positions(face)).pad(B_BOX_PADDING))
+            .collect();
+
+        let mut faces: Vec<u32> = (0..data.indices.len() as u32).collect();
+        let mut nodes = Vec::new();
+        if !faces.is_empty() {
+            Self::build(&mut nodes, &mut faces, 0, &face_boxes);
+        }
+
+        Self {
+            data,
+            material,
+            nodes,
+            faces,
+        }
+    }
+
+    pub fn data(&self) -> &MeshData {
+        &self.data
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.indices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.indices.is_empty()
+    }
+
+    // Median split along the widest axis of the face centroids
+    fn build(nodes: &mut Vec<MeshNode>, faces: &mut [u32], offset: usize, boxes: &[AAAB]) {
+        let b_box = faces
+            .iter()
+            .map(|&face| boxes[face as usize])
+            .reduce(AAAB::new_surrounding_box)
+            .unwrap();
+
+        let node_index = nodes.len();
+        nodes.push(MeshNode {
+            b_box,
+            offset: offset as u32,
+            count: faces.len() as u32,
+        });
+
+        if faces.len() <= MAX_FACES_PER_LEAF {
+            return;
+        }
+
+        let centroids: Vec<Point3<f32>> = faces
+            .iter()
+            .map(|&face| boxes[face as usize].centroid())
+            .collect();
+        let centroid_box = AAAB::surrounding_points(&centroids);
+        let extent = centroid_box.max() - centroid_box.min();
+        let axis = if extent.x() > extent.y() && extent.x() > extent.z() {
+            0
+        } else if extent.y() > extent.z() {
+            1
+        } else {
+            2
+        };
+        if extent.get(axis).unwrap() <= 0.0 {
+            // All centroids coincide, nothing to split
+            return;
+        }
+
+        let middle = faces.len() / 2;
+        faces.select_nth_unstable_by(middle, |a, b| {
+            let a = boxes[*a as usize].centroid().get(axis).unwrap();
+            let b = boxes[*b as usize].centroid().get(axis).unwrap();
+            a.partial_cmp(&b).unwrap()
+        });
+
+        let (left, right) = faces.split_at_mut(middle);
+        Self::build(nodes, left, offset, boxes);
+        let second_child = nodes.len();
+        Self::build(nodes, right, offset + middle, boxes);
+
+        nodes[node_index].offset = second_child as u32;
+        nodes[node_index].count = 0;
+    }
+
+    fn face_hit(&self, ray: &Ray, face: usize, t: f32, b1: f32, b2: f32) -> Hit {
+        let indices = self.data.indices[face];
+        let [p0, p1, p2] = self.data.face_positions(&indices);
+        let [i0, i1, i2] = [
+            indices[0] as usize,
+            indices[1] as usize,
+            indices[2] as usize,
+        ];
+        let b0 = 1.0 - b1 - b2;
+
+        let (u, v) = if self.data.uvs.is_empty() {
+            (b1, b2)
+        } else {
+            let (uv0, uv1, uv2) = (self.data.uvs[i0], self.data.uvs[i1], self.data.uvs[i2]);
+            (
+                b0 * uv0.0 + b1 * uv1.0 + b2 * uv2.0,
+                b0 * uv0.1 + b1 * uv1.1 + b2 * uv2.1,
+            )
+        };
+
+        let geometric_normal = (p1 - p0).cross(&(p2 - p0)).unit_vector();
+        let mut hit = Hit::new(
+            ray.at(t),
+            t,
+            u,
+            v,
+            self.material.clone(),
+            ray,
+            &geometric_normal,
+        );
+
+        hit.primitive = face as u32;
+        hit.barycentrics = (b1, b2);
+
+        if !self.data.normals.is_empty() {
+            let normals = &self.data.normals;
+            let mut shading_normal =
+                (b0 * normals[i0] + b1 * normals[i1] + b2 * normals[i2]).unit_vector();
+            // Keep the smooth normal on the same side as the face
+            if shading_normal.dot(&geometric_normal) < 0.0 {
+                shading_normal = -shading_normal;
+            }
+            hit.normal = if hit.is_front_facing {
+                shading_normal
+            } else {
+                -shading_normal
+            };
+        }
+
+        hit
+    }
+}
+
+impl Hittable for TriangleMesh {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+
+        let mut closest_hit_t = t_max;
+        let mut closest_face = None;
+
+        let mut stack = [0u32; MAX_DEPTH];
+        let mut stack_size = 1;
+        while stack_size > 0 {
+            stack_size -= 1;
+            let node_index = stack[stack_size];
+            let node = &self.nodes[node_index as usize];
+            if !node.b_box.is_in(ray, t_min, closest_hit_t) {
+                continue;
+            }
+
+            if node.count > 0 {
+                let start = node.offset as usize;
+                for &face in self.faces[start..start + node.count as usize].iter() {
+                    let [p0, p1, p2] = self.data.face_positions(&self.data.indices[face as usize]);
+                    if let Some((t, b1, b2)) =
+                        intersect_triangle(ray, &p0, &p1, &p2, t_min, closest_hit_t)
+                    {
+                        closest_hit_t = t;
+                        closest_face = Some((face as usize, b1, b2));
+                    }
+                }
+            } else {
+                stack[stack_size] = node.offset;
+                stack[stack_size + 1] = node_index + 1;
+                stack_size += 2;
+            }
+        }
+
+        closest_face.map(|(face, b1, b2)| self.face_hit(ray, face, closest_hit_t, b1, b2))
+    }
+
+    fn get_b_box(&self, _time0: f32, _time1: f32) -> Option<AAAB> {
+        self.nodes.first().map(|root| root.b_box)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{materials::lambertian::Lambertian, objects::triangle::Triangle};
+
+    // Bumpy grid of `size` x `size` quads in the xz plane
+    fn grid(size: u32) -> MeshData {
+        let mut data = MeshData::default();
+        for z in 0..=size {
+            for x in 0..=size {
+                let height = ((x * 7 + z * 13) % 5) as f32 * 0.1;
+                data.positions.push(Point3::new(x as f32, height, z as f32));
+            }
+        }
+        for z in 0..size {
+            for x in 0..size {
+                let corner = z * (size + 1) + x;
+                data.indices.push([corner, corner + size + 1, corner + 1]);
+                data.indices
+                    .push([corner + 1, corner + size + 1, corner + size + 2]);
+            }
+        }
+
+        data
+    }
+
+    #[test]
+    fn test_mesh_matches_individual_triangles() {
+        let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let data = grid(12);
+        let mesh = TriangleMesh::new(data.clone(), material.clone());
+
+        let triangles: Vec<Triangle> = data
+            .indices
+            .iter()
+            .map(|face| {
+                let [v0, v1, v2] = data.face_positions(face);
+                Triangle {
+                    v0,
+                    v1,
+                    v2,
+                    material: material.clone(),
+                }
+            })
+            .collect();
+
+        for i in 0..200 {
+            let origin = Point3::new(6.0, 5.0, 6.0);
+            let target = Point3::new((i % 20) as f32 * 0.61, 0.0, (i / 20) as f32 * 1.27);
+            let ray = Ray::new(origin, target - origin, 0.0);
+
+            let expected = triangles
+                .iter()
+                .filter_map(|triangle| triangle.hit(&ray, 0.001, f32::INFINITY))
+                .map(|hit| hit.t)
+                .fold(None, |closest: Option<f32>, t| {
+                    Some(closest.map_or(t, |closest| closest.min(t)))
+                });
+            let actual = mesh.hit(&ray, 0.001, f32::INFINITY).map(|hit| hit.t);
+
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_smooth_normals_face_the_ray() {
+        let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let mut data = grid(4);
+        data.compute_normals();
+        let mesh = TriangleMesh::new(data, material);
+
+        let ray = Ray::new(Point3::new(1.3, 3.0, 2.2), Vec3::new(0.0, -1.0, 0.0), 0.0);
+        let hit = mesh.hit(&ray, 0.001, f32::INFINITY).unwrap();
+
+        assert!(hit.is_front_facing);
+        assert!(hit.normal.dot(&ray.direction()) < 0.0);
+    }
+}
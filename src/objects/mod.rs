@@ -1,3 +1,21 @@
 pub mod sphere;
 pub mod plane;
-pub mod moving_sphere;
\ No newline at end of file
+pub mod moving_sphere;
+pub mod mesh;
+pub mod triangle;
+pub mod transformed;
+pub mod instance;
+pub mod box_shape;
+pub mod rect;
+pub mod disk;
+pub mod quad;
+pub mod capsule;
+pub mod cone;
+pub mod csg;
+pub mod cylinder;
+pub mod quadric;
+pub mod torus;
+pub mod sdf;
+
+// Below this the ray is considered parallel to a flat primitive
+pub(crate) const PARALLEL_EPSILON: f32 = 1e-8;
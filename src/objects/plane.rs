@@ -42,8 +42,9 @@ impl Hittable for Plane {
         ))
     }
 
+    // Infinite, so the BVH tests it on every ray
     fn get_b_box(&self, _time0: f32, _time1: f32) -> Option<AAAB> {
-        Some(AAAB::new(self.p1, self.p1))
+        None
     }
 }
 
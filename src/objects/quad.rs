@@ -0,0 +1,137 @@
+use crate::aabb::{AAAB, B_BOX_PADDING};
+use crate::{
+    hit::{Hit, Hittable},
+    materials::Material,
+    rays::Ray,
+    vectors::{Point3, Vec3},
+};
+use std::sync::Arc;
+
+use super::PARALLEL_EPSILON;
+
+/// Parallelogram spanned by the edges `u` and `v` from the corner `origin`.
+/// The hit's `u` and `v` are the coordinates along the two edges, in [0, 1].
+pub struct Quad {
+    origin: Point3<f32>,
+    u: Vec3<f32>,
+    v: Vec3<f32>,
+    normal: Vec3<f32>,
+    // Projects points of the plane onto the edges
+    w: Vec3<f32>,
+    material: Arc<dyn Material>,
+}
+
+impl Quad {
+    pub fn new(
+        origin: Point3<f32>,
+        u: Vec3<f32>,
+        v: Vec3<f32>,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        let n = u.cross(&v);
+        assert!(!n.is_near_zero(), "Quad edges must not be parallel");
+
+        Self {
+            origin,
+            u,
+            v,
+            normal: n.unit_vector(),
+            w: n / n.norm_sqr(),
+            material,
+        }
+    }
+
+    pub fn origin(&self) -> Point3<f32> {
+        self.origin
+    }
+
+    pub fn u(&self) -> Vec3<f32> {
+        self.u
+    }
+
+    pub fn v(&self) -> Vec3<f32> {
+        self.v
+    }
+
+    pub fn normal(&self) -> Vec3<f32> {
+        self.normal
+    }
+
+    pub fn area(&self) -> f32 {
+        self.u.cross(&self.v).norm()
+    }
+}
+
+impl Hittable for Quad {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit> {
+        let denominator = self.normal.dot(&ray.direction());
+        if denominator.abs() < PARALLEL_EPSILON {
+            return None;
+        }
+
+        let t = (self.origin - ray.origin()).dot(&self.normal) / denominator;
+        if t < t_min || t > t_max {
+            return None;
+        }
+
+        let point = ray.at(t);
+        let planar = point - self.origin;
+        let alpha = self.w.dot(&planar.cross(&self.v));
+        let beta = self.w.dot(&self.u.cross(&planar));
+        if !(0.0..=1.0).contains(&alpha) || !(0.0..=1.0).contains(&beta) {
+            return None;
+        }
+
+        Some(Hit::new(
+            point,
+            t,
+            alpha,
+            beta,
+            self.material.clone(),
+            ray,
+            &self.normal,
+        ))
+    }
+
+    fn get_b_box(&self, _time0: f32, _time1: f32) -> Option<AAAB> {
+        Some(
+            AAAB::surrounding_points(&[
+                self.origin,
+                self.origin + self.u,
+                self.origin + self.v,
+                self.origin + self.u + self.v,
+            ])
+            .pad(B_BOX_PADDING),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{materials::lambertian::Lambertian, rays::Color};
+
+    #[test]
+    fn test_slanted_quad() {
+        let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let quad = Quad::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(1.0, 1.0, 0.0),
+            material,
+        );
+
+        let ray = Ray::new(Point3::new(1.5, 0.5, 3.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let hit = quad.hit(&ray, 0.001, f32::INFINITY).unwrap();
+        assert!((hit.u - 0.5).abs() < 1e-6 && (hit.v - 0.5).abs() < 1e-6);
+        assert_eq!(hit.normal, Vec3::new(0.0, 0.0, 1.0));
+
+        // Inside the bounds but outside the parallelogram
+        let miss = Ray::new(Point3::new(0.2, 0.9, 3.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        assert!(quad.hit(&miss, 0.001, f32::INFINITY).is_none());
+
+        let b_box = quad.get_b_box(0.0, 0.0).unwrap();
+        assert_eq!(b_box.max().x(), 3.0);
+        assert!(b_box.max().z() > b_box.min().z());
+    }
+}
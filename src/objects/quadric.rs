@@ -0,0 +1,179 @@
+use super::sphere::get_sphere_uv;
+use crate::aabb::AAAB;
+use crate::{
+    hit::{Hit, Hittable},
+    materials::Material,
+    rays::Ray,
+    roots::solve_quadratic,
+    vectors::{matrix::Mat4, Point3, Vec3},
+};
+use std::sync::Arc;
+
+/// Surface `pᵀ Q p = 0` of a symmetric 4x4 coefficient matrix `Q`, with `p`
+/// the point in homogeneous coordinates, clipped to the `clip` box. Points
+/// where `pᵀ Q p > 0` are outside, normals point that way. `u` and `v` are
+/// the spherical angles around the clip box center.
+pub struct Quadric {
+    coefficients: Mat4,
+    clip: AAAB,
+    material: Arc<dyn Material>,
+}
+
+impl Quadric {
+    /// Only the symmetric part of `coefficients` is used.
+    pub fn new(coefficients: Mat4, clip: AAAB, material: Arc<dyn Material>) -> Self {
+        let transpose = coefficients.transpose();
+        let mut rows = [[0.0; 4]; 4];
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = 0.5 * (coefficients.get(i, j) + transpose.get(i, j));
+            }
+        }
+
+        Self {
+            coefficients: Mat4::new(rows),
+            clip,
+            material,
+        }
+    }
+
+    /// `(x - cx)² / rx² + (y - cy)² / ry² + (z - cz)² / rz² = 1`
+    pub fn ellipsoid(center: Point3<f32>, radii: Vec3<f32>, material: Arc<dyn Material>) -> Self {
+        let (a, b, c) = (
+            1.0 / (radii.x() * radii.x()),
+            1.0 / (radii.y() * radii.y()),
+            1.0 / (radii.z() * radii.z()),
+        );
+        let (x, y, z) = (center.x(), center.y(), center.z());
+        let coefficients = Mat4::new([
+            [a, 0.0, 0.0, -a * x],
+            [0.0, b, 0.0, -b * y],
+            [0.0, 0.0, c, -c * z],
+            [
+                -a * x,
+                -b * y,
+                -c * z,
+                a * x * x + b * y * y + c * z * z - 1.0,
+            ],
+        ]);
+
+        Self::new(
+            coefficients,
+            AAAB::new(center - radii, center + radii),
+            material,
+        )
+    }
+
+    pub fn coefficients(&self) -> &Mat4 {
+        &self.coefficients
+    }
+
+    pub fn clip(&self) -> AAAB {
+        self.clip
+    }
+
+    // aᵀ Q b for homogeneous vectors
+    fn form(&self, a: &[f32; 4], b: &[f32; 4]) -> f32 {
+        (0..4)
+            .map(|i| {
+                a[i] * (0..4)
+                    .map(|j| self.coefficients.get(i, j) * b[j])
+                    .sum::<f32>()
+            })
+            .sum()
+    }
+}
+
+impl Hittable for Quadric {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit> {
+        let (o, d) = (ray.origin(), ray.direction());
+        let o = [o.x(), o.y(), o.z(), 1.0];
+        let d = [d.x(), d.y(), d.z(), 0.0];
+
+        let (t0, t1) = solve_quadratic(
+            self.form(&d, &d),
+            2.0 * self.form(&d, &o),
+            self.form(&o, &o),
+        )?;
+
+        // The clip box may cut off the first root but keep the second
+        let t = [t0, t1]
+            .iter()
+            .copied()
+            .find(|&t| t >= t_min && t <= t_max && self.clip.contains(&ray.at(t)))?;
+
+        let point = ray.at(t);
+        let p = [point.x(), point.y(), point.z(), 1.0];
+        let gradient = |row: usize| (0..4).map(|j| self.coefficients.get(row, j) * p[j]).sum();
+        let outward_normal = Vec3::new(gradient(0), gradient(1), gradient(2)).unit_vector();
+        let (u, v) = get_sphere_uv(&(point - self.clip.centroid()).unit_vector());
+
+        Some(Hit::new(
+            point,
+            t,
+            u,
+            v,
+            self.material.clone(),
+            ray,
+            &outward_normal,
+        ))
+    }
+
+    fn get_b_box(&self, _time0: f32, _time1: f32) -> Option<AAAB> {
+        Some(self.clip)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{materials::lambertian::Lambertian, rays::Color};
+
+    fn material() -> Arc<dyn Material> {
+        Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)))
+    }
+
+    #[test]
+    fn test_ellipsoid() {
+        let ellipsoid = Quadric::ellipsoid(
+            Point3::new(0.0, 1.0, 0.0),
+            Vec3::new(3.0, 1.0, 1.0),
+            material(),
+        );
+
+        let ray = Ray::new(Point3::new(10.0, 1.0, 0.0), Vec3::new(-1.0, 0.0, 0.0), 0.0);
+        let hit = ellipsoid.hit(&ray, 0.001, f32::INFINITY).unwrap();
+        assert!((hit.t - 7.0).abs() < 1e-4);
+        assert!((hit.normal - Vec3::new(1.0, 0.0, 0.0)).norm() < 1e-4);
+        assert!(hit.is_front_facing);
+    }
+
+    #[test]
+    fn test_clipped_hyperboloid() {
+        // x² + z² - y² = 1, one sheet, cut at y = ±1
+        let hyperboloid = Quadric::new(
+            Mat4::new([
+                [1.0, 0.0, 0.0, 0.0],
+                [0.0, -1.0, 0.0, 0.0],
+                [0.0, 0.0, 1.0, 0.0],
+                [0.0, 0.0, 0.0, -1.0],
+            ]),
+            AAAB::new(Point3::new(-2.0, -1.0, -2.0), Point3::new(2.0, 1.0, 2.0)),
+            material(),
+        );
+
+        // The waist has radius 1
+        let waist = Ray::new(Point3::new(5.0, 0.0, 0.0), Vec3::new(-1.0, 0.0, 0.0), 0.0);
+        let hit = hyperboloid.hit(&waist, 0.001, f32::INFINITY).unwrap();
+        assert!((hit.t - 4.0).abs() < 1e-4);
+
+        // Down the open middle, the surface continues outside the clip box
+        let middle = Ray::new(Point3::new(0.0, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0), 0.0);
+        assert!(hyperboloid.hit(&middle, 0.001, f32::INFINITY).is_none());
+
+        // Cut off in front, but the inside of the far wall is still visible
+        let slanted = Ray::new(Point3::new(-3.0, 2.0, 0.0), Vec3::new(1.0, -1.0, 0.0), 0.0);
+        let hit = hyperboloid.hit(&slanted, 0.001, f32::INFINITY).unwrap();
+        assert!(hyperboloid.clip().contains(&hit.point));
+    }
+}
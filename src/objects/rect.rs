@@ -0,0 +1,240 @@
+use crate::aabb::{AAAB, B_BOX_PADDING};
+use crate::{
+    hit::{Hit, Hittable},
+    materials::Material,
+    rays::Ray,
+    vectors::{Point3, Vec3},
+};
+use std::sync::Arc;
+
+/// Rectangle in the plane `z = k`, facing +z unless `flip_normal` is set.
+pub struct XYRect {
+    pub x0: f32,
+    pub x1: f32,
+    pub y0: f32,
+    pub y1: f32,
+    pub k: f32,
+    pub flip_normal: bool,
+    pub material: Arc<dyn Material>,
+}
+
+/// Rectangle in the plane `y = k`, facing +y unless `flip_normal` is set.
+pub struct XZRect {
+    pub x0: f32,
+    pub x1: f32,
+    pub z0: f32,
+    pub z1: f32,
+    pub k: f32,
+    pub flip_normal: bool,
+    pub material: Arc<dyn Material>,
+}
+
+/// Rectangle in the plane `x = k`, facing +x unless `flip_normal` is set.
+pub struct YZRect {
+    pub y0: f32,
+    pub y1: f32,
+    pub z0: f32,
+    pub z1: f32,
+    pub k: f32,
+    pub flip_normal: bool,
+    pub material: Arc<dyn Material>,
+}
+
+impl XYRect {
+    pub fn new(x0: f32, x1: f32, y0: f32, y1: f32, k: f32, material: Arc<dyn Material>) -> Self {
+        Self {
+            x0,
+            x1,
+            y0,
+            y1,
+            k,
+            flip_normal: false,
+            material,
+        }
+    }
+
+    fn axis_rect(&self) -> AxisRect {
+        AxisRect {
+            axes: (0, 1, 2),
+            a: (self.x0, self.x1),
+            b: (self.y0, self.y1),
+            k: self.k,
+            flip_normal: self.flip_normal,
+        }
+    }
+}
+
+impl XZRect {
+    pub fn new(x0: f32, x1: f32, z0: f32, z1: f32, k: f32, material: Arc<dyn Material>) -> Self {
+        Self {
+            x0,
+            x1,
+            z0,
+            z1,
+            k,
+            flip_normal: false,
+            material,
+        }
+    }
+
+    fn axis_rect(&self) -> AxisRect {
+        AxisRect {
+            axes: (0, 2, 1),
+            a: (self.x0, self.x1),
+            b: (self.z0, self.z1),
+            k: self.k,
+            flip_normal: self.flip_normal,
+        }
+    }
+}
+
+impl YZRect {
+    pub fn new(y0: f32, y1: f32, z0: f32, z1: f32, k: f32, material: Arc<dyn Material>) -> Self {
+        Self {
+            y0,
+            y1,
+            z0,
+            z1,
+            k,
+            flip_normal: false,
+            material,
+        }
+    }
+
+    fn axis_rect(&self) -> AxisRect {
+        AxisRect {
+            axes: (1, 2, 0),
+            a: (self.y0, self.y1),
+            b: (self.z0, self.z1),
+            k: self.k,
+            flip_normal: self.flip_normal,
+        }
+    }
+}
+
+// The three rectangles only differ by which axes span them, `a` and `b` are
+// the in-plane axes and `c` the normal axis.
+struct AxisRect {
+    axes: (u32, u32, u32),
+    a: (f32, f32),
+    b: (f32, f32),
+    k: f32,
+    flip_normal: bool,
+}
+
+impl AxisRect {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32, material: &Arc<dyn Material>) -> Option<Hit> {
+        let (a_axis, b_axis, c_axis) = self.axes;
+        let origin = ray.origin();
+        let direction = ray.direction();
+
+        let t = (self.k - origin.get(c_axis)?) / direction.get(c_axis)?;
+        if !t.is_finite() || t < t_min || t > t_max {
+            return None;
+        }
+
+        let a = origin.get(a_axis)? + t * direction.get(a_axis)?;
+        let b = origin.get(b_axis)? + t * direction.get(b_axis)?;
+        if a < self.a.0 || a > self.a.1 || b < self.b.0 || b > self.b.1 {
+            return None;
+        }
+
+        let u = (a - self.a.0) / (self.a.1 - self.a.0);
+        let v = (b - self.b.0) / (self.b.1 - self.b.0);
+        let sign = if self.flip_normal { -1.0 } else { 1.0 };
+        let outward_normal = self.point(0.0, 0.0, sign);
+
+        Some(Hit::new(
+            ray.at(t),
+            t,
+            u,
+            v,
+            material.clone(),
+            ray,
+            &outward_normal,
+        ))
+    }
+
+    fn get_b_box(&self) -> AAAB {
+        AAAB::new(
+            self.point(self.a.0, self.b.0, self.k),
+            self.point(self.a.1, self.b.1, self.k),
+        )
+        .pad(B_BOX_PADDING)
+    }
+
+    // Places in-plane coordinates back on the x, y and z axes
+    fn point(&self, a: f32, b: f32, c: f32) -> Point3<f32> {
+        let mut coordinates = [0.0; 3];
+        coordinates[self.axes.0 as usize] = a;
+        coordinates[self.axes.1 as usize] = b;
+        coordinates[self.axes.2 as usize] = c;
+
+        Vec3::new(coordinates[0], coordinates[1], coordinates[2])
+    }
+}
+
+impl Hittable for XYRect {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit> {
+        self.axis_rect().hit(ray, t_min, t_max, &self.material)
+    }
+
+    fn get_b_box(&self, _time0: f32, _time1: f32) -> Option<AAAB> {
+        Some(self.axis_rect().get_b_box())
+    }
+}
+
+impl Hittable for XZRect {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit> {
+        self.axis_rect().hit(ray, t_min, t_max, &self.material)
+    }
+
+    fn get_b_box(&self, _time0: f32, _time1: f32) -> Option<AAAB> {
+        Some(self.axis_rect().get_b_box())
+    }
+}
+
+impl Hittable for YZRect {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit> {
+        self.axis_rect().hit(ray, t_min, t_max, &self.material)
+    }
+
+    fn get_b_box(&self, _time0: f32, _time1: f32) -> Option<AAAB> {
+        Some(self.axis_rect().get_b_box())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{materials::lambertian::Lambertian, rays::Color};
+
+    #[test]
+    fn test_xz_rect_hit_and_uv() {
+        let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let rect = XZRect::new(0.0, 2.0, 0.0, 4.0, 1.0, material);
+
+        let ray = Ray::new(Point3::new(0.5, 5.0, 3.0), Vec3::new(0.0, -1.0, 0.0), 0.0);
+        let hit = rect.hit(&ray, 0.001, f32::INFINITY).unwrap();
+
+        assert_eq!(hit.t, 4.0);
+        assert_eq!((hit.u, hit.v), (0.25, 0.75));
+        assert_eq!(hit.normal, Vec3::new(0.0, 1.0, 0.0));
+        assert!(hit.is_front_facing);
+
+        let miss = Ray::new(Point3::new(2.5, 5.0, 3.0), Vec3::new(0.0, -1.0, 0.0), 0.0);
+        assert!(rect.hit(&miss, 0.001, f32::INFINITY).is_none());
+    }
+
+    #[test]
+    fn test_b_box_is_thin_but_not_flat() {
+        let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let b_box = YZRect::new(0.0, 1.0, 0.0, 1.0, 3.0, material)
+            .get_b_box(0.0, 0.0)
+            .unwrap();
+
+        assert!(b_box.max().x() > b_box.min().x());
+        assert!(b_box.max().x() - b_box.min().x() < 1e-3);
+        assert_eq!(b_box.max().z(), 1.0);
+    }
+}
@@ -0,0 +1,395 @@
+use super::sphere::get_sphere_uv;
+use crate::aabb::AAAB;
+use crate::{
+    hit::{Hit, Hittable},
+    materials::Material,
+    rays::Ray,
+    vectors::{Point3, Vec3},
+};
+use std::sync::Arc;
+
+const DEFAULT_EPSILON: f32 = 1e-4;
+const DEFAULT_MAX_STEPS: u32 = 256;
+
+/// Signed distance field expression, negative inside the surface. Built from
+/// the primitive constructors and combined with the chaining methods, e.g.
+/// `Sdf::sphere(1.0).smooth_union(Sdf::cuboid(v).translate(offset), 0.2)`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Sdf {
+    Sphere {
+        radius: f32,
+    },
+    /// Box centered on the origin
+    Cuboid {
+        half_extents: Vec3<f32>,
+    },
+    RoundedBox {
+        half_extents: Vec3<f32>,
+        radius: f32,
+    },
+    /// Torus around the y axis
+    Torus {
+        major_radius: f32,
+        minor_radius: f32,
+    },
+    Translate {
+        offset: Vec3<f32>,
+        sdf: Box<Sdf>,
+    },
+    Union(Box<Sdf>, Box<Sdf>),
+    /// Union blending the surfaces over a distance of about `k`
+    SmoothUnion {
+        a: Box<Sdf>,
+        b: Box<Sdf>,
+        k: f32,
+    },
+    /// `a` with `b` carved out, blended over a distance of about `k`
+    SmoothSubtraction {
+        a: Box<Sdf>,
+        b: Box<Sdf>,
+        k: f32,
+    },
+    /// Infinite copies every `period`, axes with a zero period are not repeated
+    Repeat {
+        period: Vec3<f32>,
+        sdf: Box<Sdf>,
+    },
+    /// Rotates the xz plane by `rate` radians per unit along y
+    Twist {
+        rate: f32,
+        sdf: Box<Sdf>,
+    },
+}
+
+impl Sdf {
+    pub fn sphere(radius: f32) -> Self {
+        Sdf::Sphere { radius }
+    }
+
+    pub fn cuboid(half_extents: Vec3<f32>) -> Self {
+        Sdf::Cuboid { half_extents }
+    }
+
+    pub fn rounded_box(half_extents: Vec3<f32>, radius: f32) -> Self {
+        Sdf::RoundedBox {
+            half_extents,
+            radius,
+        }
+    }
+
+    pub fn torus(major_radius: f32, minor_radius: f32) -> Self {
+        Sdf::Torus {
+            major_radius,
+            minor_radius,
+        }
+    }
+
+    pub fn translate(self, offset: Vec3<f32>) -> Self {
+        Sdf::Translate {
+            offset,
+            sdf: Box::new(self),
+        }
+    }
+
+    pub fn union(self, other: Sdf) -> Self {
+        Sdf::Union(Box::new(self), Box::new(other))
+    }
+
+    pub fn smooth_union(self, other: Sdf, k: f32) -> Self {
+        Sdf::SmoothUnion {
+            a: Box::new(self),
+            b: Box::new(other),
+            k,
+        }
+    }
+
+    pub fn smooth_subtract(self, other: Sdf, k: f32) -> Self {
+        Sdf::SmoothSubtraction {
+            a: Box::new(self),
+            b: Box::new(other),
+            k,
+        }
+    }
+
+    pub fn repeat(self, period: Vec3<f32>) -> Self {
+        Sdf::Repeat {
+            period,
+            sdf: Box::new(self),
+        }
+    }
+
+    pub fn twist(self, rate: f32) -> Self {
+        Sdf::Twist {
+            rate,
+            sdf: Box::new(self),
+        }
+    }
+
+    pub fn distance(&self, p: &Point3<f32>) -> f32 {
+        match self {
+            Sdf::Sphere { radius } => p.norm() - radius,
+            Sdf::Cuboid { half_extents } => cuboid_distance(p, half_extents),
+            Sdf::RoundedBox {
+                half_extents,
+                radius,
+            } => {
+                let inner = *half_extents - Vec3::new(*radius, *radius, *radius);
+                cuboid_distance(p, &inner) - radius
+            }
+            Sdf::Torus {
+                major_radius,
+                minor_radius,
+            } => {
+                let ring = (p.x() * p.x() + p.z() * p.z()).sqrt() - major_radius;
+                (ring * ring + p.y() * p.y()).sqrt() - minor_radius
+            }
+            Sdf::Translate { offset, sdf } => sdf.distance(&(*p - *offset)),
+            Sdf::Union(a, b) => a.distance(p).min(b.distance(p)),
+            Sdf::SmoothUnion { a, b, k } => {
+                let (a, b) = (a.distance(p), b.distance(p));
+                let h = (0.5 + 0.5 * (b - a) / k).clamp(0.0, 1.0);
+                b + (a - b) * h - k * h * (1.0 - h)
+            }
+            Sdf::SmoothSubtraction { a, b, k } => {
+                let (a, b) = (a.distance(p), b.distance(p));
+                let h = (0.5 - 0.5 * (a + b) / k).clamp(0.0, 1.0);
+                a + (-b - a) * h + k * h * (1.0 - h)
+            }
+            Sdf::Repeat { period, sdf } => {
+                let wrap = |value: f32, period: f32| {
+                    if period == 0.0 {
+                        value
+                    } else {
+                        value - period * (value / period).round()
+                    }
+                };
+                let q = Point3::new(
+                    wrap(p.x(), period.x()),
+                    wrap(p.y(), period.y()),
+                    wrap(p.z(), period.z()),
+                );
+                sdf.distance(&q)
+            }
+            Sdf::Twist { rate, sdf } => {
+                let (sin, cos) = (rate * p.y()).sin_cos();
+                let q = Point3::new(cos * p.x() - sin * p.z(), p.y(), sin * p.x() + cos * p.z());
+                sdf.distance(&q)
+            }
+        }
+    }
+}
+
+fn cuboid_distance(p: &Point3<f32>, half_extents: &Vec3<f32>) -> f32 {
+    let q = Vec3::new(
+        p.x().abs() - half_extents.x(),
+        p.y().abs() - half_extents.y(),
+        p.z().abs() - half_extents.z(),
+    );
+    let outside = Vec3::new(q.x().max(0.0), q.y().max(0.0), q.z().max(0.0)).norm();
+    let inside = q.x().max(q.y()).max(q.z()).min(0.0);
+
+    outside + inside
+}
+
+/// Surface of an `Sdf`, found by sphere tracing inside the user supplied
+/// `b_box`, which has to contain the whole surface. `u` and `v` are the
+/// spherical angles around the box center.
+pub struct SdfObject {
+    sdf: Sdf,
+    b_box: AAAB,
+    material: Arc<dyn Material>,
+    epsilon: f32,
+    max_steps: u32,
+    step_scale: f32,
+}
+
+impl SdfObject {
+    pub fn new(sdf: Sdf, b_box: AAAB, material: Arc<dyn Material>) -> Self {
+        Self {
+            sdf,
+            b_box,
+            material,
+            epsilon: DEFAULT_EPSILON,
+            max_steps: DEFAULT_MAX_STEPS,
+            step_scale: 1.0,
+        }
+    }
+
+    /// Distance to the surface at which a ray counts as hitting it.
+    pub fn set_epsilon(&mut self, epsilon: f32) {
+        self.epsilon = epsilon;
+    }
+
+    /// Rays that have not reached the surface after this many steps miss.
+    pub fn set_max_steps(&mut self, max_steps: u32) {
+        self.max_steps = max_steps;
+    }
+
+    /// Fraction of the distance bound taken per step. Twists and other
+    /// deformations overestimate the distance, lower this if they show holes.
+    pub fn set_step_scale(&mut self, step_scale: f32) {
+        self.step_scale = step_scale;
+    }
+
+    pub fn sdf(&self) -> &Sdf {
+        &self.sdf
+    }
+
+    // Tetrahedral central differences, four evaluations instead of six
+    fn gradient(&self, p: &Point3<f32>) -> Vec3<f32> {
+        let h = self.epsilon;
+        [
+            Vec3::new(1.0, -1.0, -1.0),
+            Vec3::new(-1.0, -1.0, 1.0),
+            Vec3::new(-1.0, 1.0, -1.0),
+            Vec3::new(1.0, 1.0, 1.0),
+        ]
+        .iter()
+        .fold(Vec3::default(), |sum, k| {
+            sum + self.sdf.distance(&(*p + h * *k)) * *k
+        })
+    }
+}
+
+impl Hittable for SdfObject {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit> {
+        let (t_near, t_far) = self.b_box.ray_range(ray, t_min, t_max)?;
+        let speed = ray.direction().norm();
+
+        let mut t = t_near;
+        // Which side of the surface the ray starts on, unknown while it is
+        // still within epsilon of the surface it may have just left
+        let mut side = 0.0;
+        let mut hit_t = None;
+
+        for _ in 0..self.max_steps {
+            if t > t_far {
+                return None;
+            }
+
+            let distance = self.sdf.distance(&ray.at(t));
+            if side == 0.0 {
+                if distance.abs() < self.epsilon {
+                    t += self.epsilon / speed;
+                    continue;
+                }
+                side = distance.signum();
+            }
+
+            let distance = side * distance;
+            if distance < self.epsilon {
+                hit_t = Some(t);
+                break;
+            }
+            t += self.step_scale * distance / speed;
+        }
+
+        let t = hit_t?;
+        let point = ray.at(t);
+        let gradient = self.gradient(&point);
+        // Flat spots like a sphere's center have no gradient, face the ray
+        let outward_normal = if gradient.is_near_zero() {
+            -ray.direction().unit_vector()
+        } else {
+            gradient.unit_vector()
+        };
+        let (u, v) = get_sphere_uv(&(point - self.b_box.centroid()).unit_vector());
+
+        Some(Hit::new(
+            point,
+            t,
+            u,
+            v,
+            self.material.clone(),
+            ray,
+            &outward_normal,
+        ))
+    }
+
+    fn get_b_box(&self, _time0: f32, _time1: f32) -> Option<AAAB> {
+        Some(self.b_box)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{materials::lambertian::Lambertian, rays::Color};
+
+    fn object(sdf: Sdf, size: f32) -> SdfObject {
+        SdfObject::new(
+            sdf,
+            AAAB::new(
+                Point3::new(-size, -size, -size),
+                Point3::new(size, size, size),
+            ),
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        )
+    }
+
+    #[test]
+    fn test_distances() {
+        let p = Point3::new(3.0, 0.0, 0.0);
+
+        assert_eq!(Sdf::sphere(1.0).distance(&p), 2.0);
+        assert_eq!(Sdf::cuboid(Vec3::new(1.0, 1.0, 1.0)).distance(&p), 2.0);
+        assert_eq!(Sdf::torus(2.0, 0.5).distance(&p), 0.5);
+        assert_eq!(
+            Sdf::sphere(1.0)
+                .repeat(Vec3::new(5.0, 0.0, 0.0))
+                .translate(Vec3::new(10.0, 0.0, 0.0))
+                .distance(&p),
+            1.0
+        );
+
+        // Blending pulls the union out between the two spheres
+        let a = Sdf::sphere(1.0).translate(Vec3::new(-1.5, 0.0, 0.0));
+        let b = Sdf::sphere(1.0).translate(Vec3::new(1.5, 0.0, 0.0));
+        let origin = Point3::new(0.0, 0.0, 0.0);
+        assert_eq!(a.clone().union(b.clone()).distance(&origin), 0.5);
+        assert!(a.smooth_union(b, 1.0).distance(&origin) < 0.5);
+    }
+
+    #[test]
+    fn test_sphere_tracing_matches_sphere() {
+        let sphere = object(Sdf::sphere(1.0), 2.0);
+
+        let ray = Ray::new(Point3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -2.0), 0.0);
+        let hit = sphere.hit(&ray, 0.001, f32::INFINITY).unwrap();
+        assert!((hit.t - 2.0).abs() < 1e-3);
+        assert!((hit.normal - Vec3::new(0.0, 0.0, 1.0)).norm() < 1e-2);
+        assert!(hit.is_front_facing);
+
+        // From just inside the surface, out the other side
+        let inside = Ray::new(hit.point, Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let exit = sphere.hit(&inside, 0.001, f32::INFINITY).unwrap();
+        assert!((exit.point.z() + 1.0).abs() < 1e-3);
+        assert!(!exit.is_front_facing);
+
+        let miss = Ray::new(Point3::new(1.5, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        assert!(sphere.hit(&miss, 0.001, f32::INFINITY).is_none());
+    }
+
+    #[test]
+    fn test_smooth_subtraction_carves() {
+        let carved = object(
+            Sdf::rounded_box(Vec3::new(1.0, 1.0, 1.0), 0.1)
+                .smooth_subtract(Sdf::sphere(0.5).translate(Vec3::new(0.0, 0.0, 1.0)), 0.05),
+            2.0,
+        );
+
+        let ray = Ray::new(Point3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let hit = carved.hit(&ray, 0.001, f32::INFINITY).unwrap();
+        assert!(hit.point.z() < 0.6 && hit.point.z() > 0.4);
+    }
+
+    #[test]
+    fn test_zero_gradient_faces_the_ray() {
+        // The ray lands exactly on the center of a point-sized sphere
+        let point = object(Sdf::sphere(0.0), 2.0);
+        let ray = Ray::new(Point3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let hit = point.hit(&ray, 0.001, f32::INFINITY).unwrap();
+
+        assert_eq!(hit.normal, Vec3::new(0.0, 0.0, 1.0));
+    }
+}
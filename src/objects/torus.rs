@@ -0,0 +1,189 @@
+use super::{cylinder::angle_u, disk::disk_b_box};
+use crate::aabb::AAAB;
+use crate::{
+    hit::{Hit, Hittable},
+    materials::Material,
+    rays::Ray,
+    roots::{solve_quadratic, solve_quartic},
+    vectors::{onb::Onb, Point3, Vec3},
+};
+use std::{f32::consts::PI, sync::Arc};
+
+/// Ring shaped torus around `axis`, with the tube of `minor_radius` running
+/// at `major_radius` from `center`. `u` goes around the axis and `v` around
+/// the tube.
+pub struct Torus {
+    center: Point3<f32>,
+    major_radius: f32,
+    minor_radius: f32,
+    material: Arc<dyn Material>,
+    // Local frame with the axis along w
+    onb: Onb,
+}
+
+impl Torus {
+    pub fn new(
+        center: Point3<f32>,
+        axis: Vec3<f32>,
+        major_radius: f32,
+        minor_radius: f32,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        assert!(
+            0.0 < minor_radius && minor_radius < major_radius,
+            "Torus needs 0 < minor_radius < major_radius"
+        );
+
+        Self {
+            center,
+            major_radius,
+            minor_radius,
+            material,
+            onb: Onb::from_w(&axis),
+        }
+    }
+
+    pub fn center(&self) -> Point3<f32> {
+        self.center
+    }
+
+    pub fn axis(&self) -> Vec3<f32> {
+        self.onb.w
+    }
+
+    pub fn major_radius(&self) -> f32 {
+        self.major_radius
+    }
+
+    pub fn minor_radius(&self) -> f32 {
+        self.minor_radius
+    }
+}
+
+impl Hittable for Torus {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit> {
+        let o = self.onb.to_local(&(ray.origin() - self.center));
+        let d = self.onb.to_local(&ray.direction());
+
+        // Quartic coefficients lose precision with the distance to the torus,
+        // so the origin is first moved onto its bounding sphere
+        let outer = self.major_radius + self.minor_radius;
+        let (enter, exit) =
+            solve_quadratic(d.norm_sqr(), 2.0 * o.dot(&d), o.norm_sqr() - outer * outer)?;
+        if exit < t_min || enter > t_max {
+            return None;
+        }
+        let shift = enter as f64;
+
+        let (dx, dy, dz) = (d.x() as f64, d.y() as f64, d.z() as f64);
+        let (ox, oy, oz) = (
+            o.x() as f64 + shift * dx,
+            o.y() as f64 + shift * dy,
+            o.z() as f64 + shift * dz,
+        );
+        let major2 = (self.major_radius as f64).powi(2);
+        let minor2 = (self.minor_radius as f64).powi(2);
+
+        // (|p|² - R² - r²)² + 4 R² z² = 4 R² r²
+        let dd = dx * dx + dy * dy + dz * dz;
+        let od = ox * dx + oy * dy + oz * dz;
+        let e = ox * ox + oy * oy + oz * oz - major2 - minor2;
+        let roots = solve_quartic([
+            e * e + 4.0 * major2 * (oz * oz - minor2),
+            4.0 * od * e + 8.0 * major2 * oz * dz,
+            2.0 * dd * e + 4.0 * od * od + 4.0 * major2 * dz * dz,
+            4.0 * dd * od,
+            dd * dd,
+        ]);
+
+        let t = roots
+            .iter()
+            .map(|root| (root + shift) as f32)
+            .find(|t| *t >= t_min && *t <= t_max)?;
+
+        let p = o + t * d;
+        let ring_distance = (p.x() * p.x() + p.y() * p.y()).sqrt();
+        let scale = 1.0 - self.major_radius / ring_distance;
+        let normal = Vec3::new(p.x() * scale, p.y() * scale, p.z()).unit_vector();
+        let tube_angle = p.z().atan2(ring_distance - self.major_radius) + PI;
+
+        Some(Hit::new(
+            ray.at(t),
+            t,
+            angle_u(&p),
+            tube_angle / (2.0 * PI),
+            self.material.clone(),
+            ray,
+            &self.onb.local(&normal),
+        ))
+    }
+
+    fn get_b_box(&self, _time0: f32, _time1: f32) -> Option<AAAB> {
+        let ring = disk_b_box(&self.center, &self.onb.w, self.major_radius);
+        let tube = Vec3::new(self.minor_radius, self.minor_radius, self.minor_radius);
+
+        Some(AAAB::new(ring.min() - tube, ring.max() + tube))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{materials::lambertian::Lambertian, rays::Color};
+
+    fn torus() -> Torus {
+        Torus::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            2.0,
+            0.5,
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        )
+    }
+
+    #[test]
+    fn test_hits_both_sides_of_the_hole() {
+        let torus = torus();
+        let ray = Ray::new(Point3::new(-10.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), 0.0);
+
+        let expected = [7.5, 8.5, 11.5, 12.5];
+        let mut t_min = 0.001;
+        for t in expected {
+            let hit = torus.hit(&ray, t_min, f32::INFINITY).unwrap();
+            assert!((hit.t - t).abs() < 1e-4, "{} != {}", hit.t, t);
+            t_min = hit.t + 0.001;
+        }
+        assert!(torus.hit(&ray, t_min, f32::INFINITY).is_none());
+
+        // Straight through the hole
+        let hole = Ray::new(Point3::new(0.0, 10.0, 0.0), Vec3::new(0.0, -1.0, 0.0), 0.0);
+        assert!(torus.hit(&hole, 0.001, f32::INFINITY).is_none());
+    }
+
+    #[test]
+    fn test_far_away_hit_normal() {
+        let torus = torus();
+        let ray = Ray::new(Point3::new(2.0, 1e4, 0.0), Vec3::new(0.0, -1.0, 0.0), 0.0);
+        let hit = torus.hit(&ray, 0.001, f32::INFINITY).unwrap();
+
+        assert!((hit.point - Point3::new(2.0, 0.5, 0.0)).norm() < 1e-2);
+        assert!((hit.normal - Vec3::new(0.0, 1.0, 0.0)).norm() < 1e-3);
+    }
+
+    #[test]
+    fn test_oblique_hit_lies_on_surface() {
+        let torus = torus();
+        let target = Point3::new(2.4, 0.3, 0.0);
+        let origin = Point3::new(5.6, 3.9, 1.5);
+        let ray = Ray::new(origin, target - origin, 0.0);
+        let hit = torus.hit(&ray, 0.001, f32::INFINITY).unwrap();
+
+        // Distance from the tube's center circle is the minor radius
+        let p = hit.point;
+        let ring_distance = (p.x() * p.x() + p.z() * p.z()).sqrt() - 2.0;
+        let tube_distance = (ring_distance * ring_distance + p.y() * p.y()).sqrt();
+        assert!((tube_distance - 0.5).abs() < 1e-4);
+        assert!((hit.t - 1.0).abs() < 1e-4);
+        assert!((hit.normal - Vec3::new(0.8, 0.6, 0.0)).norm() < 1e-3);
+    }
+}
@@ -0,0 +1,103 @@
+use crate::{
+    aabb::AAAB,
+    hit::{Hit, Hittable},
+    rays::Ray,
+    vectors::transform::Transform,
+};
+
+/// Places any `Hittable` in the world through an affine transform, the
+/// wrapped object stays in its own object space.
+pub struct Transformed<H: Hittable> {
+    pub object: H,
+    pub transform: Transform,
+}
+
+impl<H: Hittable> Transformed<H> {
+    pub fn new(object: H, transform: Transform) -> Self {
+        Self { object, transform }
+    }
+
+    // The direction is not normalized, so `t` is the same in both spaces
+    pub(crate) fn to_object_ray(&self, ray: &Ray) -> Ray {
+        Ray::new(
+            self.transform.inverse_transform_point(&ray.origin()),
+            self.transform.inverse_transform_vector(&ray.direction()),
+            ray.time(),
+        )
+    }
+
+    pub(crate) fn to_world_hit(&self, mut hit: Hit) -> Hit {
+        hit.point = self.transform.transform_point(&hit.point);
+        // The inverse transpose keeps the normal on the ray's side
+        hit.normal = self.transform.transform_normal(&hit.normal).unit_vector();
+
+        hit
+    }
+}
+
+impl<H: Hittable> Hittable for Transformed<H> {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit> {
+        let hit = self.object.hit(&self.to_object_ray(ray), t_min, t_max)?;
+
+        Some(self.to_world_hit(hit))
+    }
+
+    fn get_b_box(&self, time0: f32, time1: f32) -> Option<AAAB> {
+        self.object
+            .get_b_box(time0, time1)
+            .map(|b_box| self.transform.transform_b_box(&b_box))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        materials::lambertian::Lambertian,
+        objects::sphere::Sphere,
+        rays::Color,
+        vectors::{Point3, Vec3},
+    };
+    use std::sync::Arc;
+
+    fn unit_sphere() -> Sphere {
+        Sphere {
+            center: Point3::new(0.0, 0.0, 0.0),
+            radius: 1.0,
+            material: Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        }
+    }
+
+    #[test]
+    fn test_scaled_sphere_hit() {
+        // Ellipsoid with radius 4 along x
+        let transform = Transform::scaling(Vec3::new(4.0, 1.0, 1.0))
+            .then(&Transform::translation(Vec3::new(0.0, 0.0, -10.0)));
+        let ellipsoid = Transformed::new(unit_sphere(), transform);
+
+        let ray = Ray::new(
+            Point3::new(10.0, 0.0, -10.0),
+            Vec3::new(-1.0, 0.0, 0.0),
+            0.0,
+        );
+        let hit = ellipsoid.hit(&ray, 0.001, f32::INFINITY).unwrap();
+
+        assert!((hit.t - 6.0).abs() < 1e-4);
+        assert!((hit.point - Point3::new(4.0, 0.0, -10.0)).norm() < 1e-4);
+        assert!((hit.normal - Vec3::new(1.0, 0.0, 0.0)).norm() < 1e-4);
+        assert!(hit.is_front_facing);
+    }
+
+    #[test]
+    fn test_rotated_b_box() {
+        let transform = Transform::scaling(Vec3::new(2.0, 1.0, 1.0))
+            .then(&Transform::rotation_z(90.0))
+            .then(&Transform::translation(Vec3::new(0.0, 0.0, 5.0)));
+        let b_box = Transformed::new(unit_sphere(), transform)
+            .get_b_box(0.0, 0.0)
+            .unwrap();
+
+        assert!((b_box.min() - Point3::new(-1.0, -2.0, 4.0)).norm() < 1e-4);
+        assert!((b_box.max() - Point3::new(1.0, 2.0, 6.0)).norm() < 1e-4);
+    }
+}
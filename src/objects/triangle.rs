@@ -0,0 +1,114 @@
+use crate::aabb::{AAAB, B_BOX_PADDING};
+use crate::{
+    hit::{Hit, Hittable},
+    materials::Material,
+    rays::Ray,
+    vectors::{Point3, Vec3},
+};
+use std::sync::Arc;
+
+use super::PARALLEL_EPSILON;
+
+/// Single triangle, with `u` and `v` being the barycentric coordinates of the
+/// hit with respect to `v1` and `v2`. Use `TriangleMesh` for many triangles.
+pub struct Triangle {
+    pub v0: Point3<f32>,
+    pub v1: Point3<f32>,
+    pub v2: Point3<f32>,
+    pub material: Arc<dyn Material>,
+}
+
+impl Hittable for Triangle {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit> {
+        let (t, u, v) = intersect_triangle(ray, &self.v0, &self.v1, &self.v2, t_min, t_max)?;
+        let outward_normal = (self.v1 - self.v0)
+            .cross(&(self.v2 - self.v0))
+            .unit_vector();
+
+        Some(Hit::new(
+            ray.at(t),
+            t,
+            u,
+            v,
+            self.material.clone(),
+            ray,
+            &outward_normal,
+        ))
+    }
+
+    fn get_b_box(&self, _time0: f32, _time1: f32) -> Option<AAAB> {
+        Some(AAAB::surrounding_points(&[self.v0, self.v1, self.v2]).pad(B_BOX_PADDING))
+    }
+}
+
+/// Möller–Trumbore ray/triangle intersection. Returns the ray parameter and
+/// the barycentric coordinates of the hit with respect to `v1` and `v2`.
+pub fn intersect_triangle(
+    ray: &Ray,
+    v0: &Point3<f32>,
+    v1: &Point3<f32>,
+    v2: &Point3<f32>,
+    t_min: f32,
+    t_max: f32,
+) -> Option<(f32, f32, f32)> {
+    let edge1: Vec3<f32> = *v1 - *v0;
+    let edge2: Vec3<f32> = *v2 - *v0;
+
+    let p = ray.direction().cross(&edge2);
+    let determinant = edge1.dot(&p);
+    if determinant.abs() < PARALLEL_EPSILON {
+        return None;
+    }
+    let inverse_determinant = 1.0 / determinant;
+
+    let s = ray.origin() - *v0;
+    let u = s.dot(&p) * inverse_determinant;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let q = s.cross(&edge1);
+    let v = ray.direction().dot(&q) * inverse_determinant;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let t = edge2.dot(&q) * inverse_determinant;
+    if t < t_min || t > t_max {
+        return None;
+    }
+
+    Some((t, u, v))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit_triangle(ray: &Ray) -> Option<(f32, f32, f32)> {
+        intersect_triangle(
+            ray,
+            &Point3::new(0.0, 0.0, 0.0),
+            &Point3::new(1.0, 0.0, 0.0),
+            &Point3::new(0.0, 1.0, 0.0),
+            0.0,
+            f32::INFINITY,
+        )
+    }
+
+    #[test]
+    fn test_hit_barycentrics() {
+        let ray = Ray::new(Point3::new(0.25, 0.5, 1.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+
+        assert_eq!(unit_triangle(&ray), Some((1.0, 0.25, 0.5)));
+    }
+
+    #[test]
+    fn test_miss_outside_and_parallel() {
+        let outside = Ray::new(Point3::new(0.8, 0.8, 1.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let parallel = Ray::new(Point3::new(0.1, 0.1, 1.0), Vec3::new(1.0, 0.0, 0.0), 0.0);
+
+        assert_eq!(unit_triangle(&outside), None);
+        assert_eq!(unit_triangle(&parallel), None);
+    }
+}
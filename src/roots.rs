@@ -0,0 +1,232 @@
+//! Polynomial root finding for the analytic primitives.
+
+/// Real roots of `a x² + b x + c`, in increasing order. Falls back to the
+/// linear equation when `a` is zero, in which case both roots are the same.
+pub fn solve_quadratic(a: f32, b: f32, c: f32) -> Option<(f32, f32)> {
+    if a == 0.0 {
+        if b == 0.0 {
+            return None;
+        }
+        let root = -c / b;
+        return Some((root, root));
+    }
+
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+
+    // Avoids the cancellation of -b + sqrt(discriminant) when b is large
+    let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+    if q == 0.0 {
+        return Some((0.0, 0.0));
+    }
+    let (root0, root1) = (q / a, c / q);
+
+    Some((root0.min(root1), root0.max(root1)))
+}
+
+// Coefficients this close to zero are treated as zero
+const ROOT_EPSILON: f64 = 1e-9;
+// Refinement steps per quartic root, Newton usually needs far fewer
+const MAX_ITERATIONS: usize = 64;
+
+/// Up to four real roots, in increasing order.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Roots {
+    values: [f64; 4],
+    len: usize,
+}
+
+impl Roots {
+    fn push(&mut self, root: f64) {
+        self.values[self.len] = root;
+        self.len += 1;
+    }
+
+    fn sort(&mut self) {
+        self.values[..self.len].sort_by(f64::total_cmp);
+    }
+}
+
+impl std::ops::Deref for Roots {
+    type Target = [f64];
+
+    fn deref(&self) -> &[f64] {
+        &self.values[..self.len]
+    }
+}
+
+/// Real roots of `x³ + a x² + b x + c`, using Cardano's formula or the
+/// trigonometric form when there are three of them.
+pub fn solve_normalized_cubic(a: f64, b: f64, c: f64) -> Roots {
+    let mut roots = Roots::default();
+
+    // Substitute x = y - a / 3 to get y³ + 3 p y + 2 q
+    let a2 = a * a;
+    let p = (b - a2 / 3.0) / 3.0;
+    let q = (2.0 / 27.0 * a * a2 - a * b / 3.0 + c) / 2.0;
+    let p3 = p * p * p;
+    let discriminant = q * q + p3;
+
+    if discriminant.abs() < ROOT_EPSILON {
+        if q.abs() < ROOT_EPSILON {
+            roots.push(0.0);
+        } else {
+            let u = (-q).cbrt();
+            roots.push(2.0 * u);
+            roots.push(-u);
+        }
+    } else if discriminant < 0.0 {
+        let phi = (-q / (-p3).sqrt()).clamp(-1.0, 1.0).acos() / 3.0;
+        let t = 2.0 * (-p).sqrt();
+        roots.push(t * phi.cos());
+        roots.push(-t * (phi + std::f64::consts::FRAC_PI_3).cos());
+        roots.push(-t * (phi - std::f64::consts::FRAC_PI_3).cos());
+    } else {
+        let sqrt_discriminant = discriminant.sqrt();
+        roots.push((sqrt_discriminant - q).cbrt() - (sqrt_discriminant + q).cbrt());
+    }
+
+    for root in roots.values[..roots.len].iter_mut() {
+        *root -= a / 3.0;
+    }
+    roots.sort();
+
+    roots
+}
+
+/// Real roots of `c[4] x⁴ + c[3] x³ + c[2] x² + c[1] x + c[0]`. The closed
+/// form is too fragile in `f32` scenes, so the roots are instead bracketed
+/// between the critical points (the roots of the derivative) and refined with
+/// Newton steps that fall back to bisection. Roots of even multiplicity do not
+/// change sign and are only found if hit exactly, which for ray tracing means
+/// grazing hits may be missed.
+pub fn solve_quartic(c: [f64; 5]) -> Roots {
+    if c[4].abs() < ROOT_EPSILON {
+        if c[3].abs() < ROOT_EPSILON {
+            return Roots::default();
+        }
+        return solve_normalized_cubic(c[2] / c[3], c[1] / c[3], c[0] / c[3]);
+    }
+
+    let c = [c[0] / c[4], c[1] / c[4], c[2] / c[4], c[3] / c[4], 1.0];
+    let evaluate = |x: f64| (((x + c[3]) * x + c[2]) * x + c[1]) * x + c[0];
+    let derive = |x: f64| ((4.0 * x + 3.0 * c[3]) * x + 2.0 * c[2]) * x + c[1];
+
+    // Cauchy's bound, all roots are within it
+    let bound = 1.0
+        + c[..4]
+            .iter()
+            .fold(0.0f64, |max, value| max.max(value.abs()));
+
+    let mut bounds = [0.0; 5];
+    let mut len = 0;
+    bounds[len] = -bound;
+    len += 1;
+    for &critical in solve_normalized_cubic(0.75 * c[3], 0.5 * c[2], 0.25 * c[1]).iter() {
+        if critical > bounds[len - 1] && critical < bound {
+            bounds[len] = critical;
+            len += 1;
+        }
+    }
+    bounds[len] = bound;
+    len += 1;
+
+    // The quartic is monotonic between consecutive bounds
+    let mut roots = Roots::default();
+    for interval in bounds[..len].windows(2) {
+        let (mut low, mut high) = (interval[0], interval[1]);
+        let (f_low, f_high) = (evaluate(low), evaluate(high));
+        if f_low == 0.0 {
+            roots.push(low);
+            continue;
+        }
+        if f_low.signum() == f_high.signum() {
+            continue;
+        }
+
+        let rising = f_low < 0.0;
+        let mut x = 0.5 * (low + high);
+        for _ in 0..MAX_ITERATIONS {
+            let value = evaluate(x);
+            if value == 0.0 {
+                break;
+            }
+            if (value < 0.0) == rising {
+                low = x;
+            } else {
+                high = x;
+            }
+
+            let newton = x - value / derive(x);
+            let next = if newton > low && newton < high {
+                newton
+            } else {
+                0.5 * (low + high)
+            };
+            if (next - x).abs() <= f64::EPSILON * x.abs().max(1.0) {
+                x = next;
+                break;
+            }
+            x = next;
+        }
+        roots.push(x);
+    }
+
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve_quadratic() {
+        assert_eq!(solve_quadratic(1.0, -3.0, 2.0), Some((1.0, 2.0)));
+        assert_eq!(solve_quadratic(0.0, 2.0, -4.0), Some((2.0, 2.0)));
+        assert_eq!(solve_quadratic(1.0, 0.0, 1.0), None);
+
+        // Large b, small root
+        let (small, _) = solve_quadratic(1.0, 1e4, -1.0).unwrap();
+        assert!((small - -1e4).abs() < 1.0);
+        let (_, tiny) = solve_quadratic(1.0, 1e4, -1.0).unwrap();
+        assert!((tiny - 1e-4).abs() < 1e-8);
+    }
+
+    #[test]
+    fn test_solve_cubic() {
+        // (x - 1)(x - 2)(x + 3)
+        let roots = solve_normalized_cubic(0.0, -7.0, 6.0);
+        let expected = [-3.0, 1.0, 2.0];
+
+        assert_eq!(roots.len(), 3);
+        for (root, expected) in roots.iter().zip(expected.iter()) {
+            assert!((root - expected).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_solve_quartic() {
+        // 2 (x + 2)(x - 0.5)(x - 1)(x - 3)
+        let roots = solve_quartic([-6.0, 17.0, -8.0, -5.0, 2.0]);
+        let expected = [-2.0, 0.5, 1.0, 3.0];
+
+        assert_eq!(roots.len(), 4);
+        for (root, expected) in roots.iter().zip(expected.iter()) {
+            assert!((root - expected).abs() < 1e-9);
+        }
+
+        // (x² + 1)(x - 1)(x - 2), only two real roots
+        let roots = solve_quartic([2.0, -3.0, 3.0, -3.0, 1.0]);
+        assert_eq!(roots.len(), 2);
+        assert!((roots[0] - 1.0).abs() < 1e-9 && (roots[1] - 2.0).abs() < 1e-9);
+
+        // (x - 0.5)²(x - 0.2)(x - 5), the double root is a tangent hit
+        let roots = solve_quartic([0.25, -2.3, 6.45, -6.2, 1.0]);
+        assert!(roots.iter().any(|root| (root - 0.5).abs() < 1e-4));
+
+        // x⁴ + 1 has no real roots
+        assert!(solve_quartic([1.0, 0.0, 0.0, 0.0, 1.0]).is_empty());
+    }
+}
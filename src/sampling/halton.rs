@@ -0,0 +1,70 @@
+use super::{mix_bits, pcg::Pcg32, pixel_sample_hash, Sampler, ONE_MINUS_EPSILON};
+
+const PRIMES: [u64; 32] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89,
+    97, 101, 103, 107, 109, 113, 127, 131,
+];
+
+/// Halton sequence, one prime base per dimension. Each pixel gets its own
+/// random shift (Cranley-Patterson rotation) per dimension. Dimensions past
+/// the prime table fall back to independent random numbers.
+pub struct HaltonSampler {
+    seed: u64,
+    pixel_hash: u64,
+    index: u32,
+    dimension: u32,
+    rng: Pcg32,
+}
+
+impl HaltonSampler {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            pixel_hash: 0,
+            index: 0,
+            dimension: 0,
+            rng: Pcg32::new(seed),
+        }
+    }
+}
+
+impl Sampler for HaltonSampler {
+    fn start_pixel_sample(&mut self, x: u32, y: u32, index: u32) {
+        self.pixel_hash = pixel_sample_hash(self.seed, x, y, u32::MAX);
+        self.index = index;
+        self.dimension = 0;
+        self.rng = Pcg32::new(pixel_sample_hash(self.seed, x, y, index));
+    }
+
+    fn next_1d(&mut self) -> f32 {
+        let dimension = self.dimension as usize;
+        self.dimension += 1;
+
+        match PRIMES.get(dimension) {
+            Some(&base) => {
+                let shift = Pcg32::new(mix_bits(self.pixel_hash, dimension as u64)).next_f32();
+                let value = radical_inverse(base, self.index as u64) + shift;
+
+                value.fract().min(ONE_MINUS_EPSILON)
+            }
+            None => self.rng.next_f32(),
+        }
+    }
+}
+
+/// Mirrors the digits of `index` in the given base around the decimal point
+pub fn radical_inverse(base: u64, mut index: u64) -> f32 {
+    let inverse_base = 1.0 / base as f64;
+    let mut reversed_digits = 0;
+    let mut inverse_base_power = 1.0;
+
+    while index > 0 {
+        let next = index / base;
+        let digit = index - next * base;
+        reversed_digits = reversed_digits * base + digit;
+        inverse_base_power *= inverse_base;
+        index = next;
+    }
+
+    ((reversed_digits as f64 * inverse_base_power) as f32).min(ONE_MINUS_EPSILON)
+}
@@ -0,0 +1,26 @@
+use super::{pcg::Pcg32, pixel_sample_hash, Sampler};
+
+/// Uniform random numbers, independent from each other
+pub struct IndependentSampler {
+    seed: u64,
+    rng: Pcg32,
+}
+
+impl IndependentSampler {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: Pcg32::new(seed),
+        }
+    }
+}
+
+impl Sampler for IndependentSampler {
+    fn start_pixel_sample(&mut self, x: u32, y: u32, index: u32) {
+        self.rng = Pcg32::new(pixel_sample_hash(self.seed, x, y, index));
+    }
+
+    fn next_1d(&mut self) -> f32 {
+        self.rng.next_f32()
+    }
+}
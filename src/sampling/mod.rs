@@ -0,0 +1,195 @@
+pub mod halton;
+pub mod independent;
+pub mod pcg;
+pub mod sobol;
+pub mod stratified;
+
+use halton::HaltonSampler;
+use independent::IndependentSampler;
+use sobol::SobolSampler;
+use stratified::StratifiedSampler;
+
+/// Largest f32 below one
+pub const ONE_MINUS_EPSILON: f32 = 1.0 - f32::EPSILON / 2.0;
+
+/// Source of the random numbers used while tracing a path. Each call draws from
+/// the next dimension of the sample: the renderer takes the pixel jitter first,
+/// then the lens, then the shutter time, then whatever the bounces need. Samplers are
+/// reseeded for every pixel sample, so the numbers a sample gets depend only on
+/// the render seed, the pixel and the sample index, never on which thread
+/// rendered it or in what order.
+pub trait Sampler {
+    /// Prepares the sampler for the `index`-th sample of pixel (`x`, `y`)
+    fn start_pixel_sample(&mut self, x: u32, y: u32, index: u32);
+
+    /// Uniform number in [0, 1)
+    fn next_1d(&mut self) -> f32;
+
+    fn next_2d(&mut self) -> (f32, f32) {
+        (self.next_1d(), self.next_1d())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SamplerKind {
+    Independent,
+    Stratified,
+    Halton,
+    Sobol,
+}
+
+impl SamplerKind {
+    pub fn build(self, seed: u64, samples_per_pixel: u32) -> Box<dyn Sampler> {
+        match self {
+            SamplerKind::Independent => Box::new(IndependentSampler::new(seed)),
+            SamplerKind::Stratified => Box::new(StratifiedSampler::new(seed, samples_per_pixel)),
+            SamplerKind::Halton => Box::new(HaltonSampler::new(seed)),
+            SamplerKind::Sobol => Box::new(SobolSampler::new(seed)),
+        }
+    }
+}
+
+/// Combines two values into a well distributed 64-bit hash (SplitMix64 finalizer).
+pub fn mix_bits(a: u64, b: u64) -> u64 {
+    let mut z = a ^ b.wrapping_add(0x9e37_79b9_7f4a_7c15).wrapping_add(a << 6);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Hash identifying a single sample of a single pixel, for a given render seed
+pub fn pixel_sample_hash(seed: u64, x: u32, y: u32, index: u32) -> u64 {
+    let pixel = ((x as u64) << 32) | y as u64;
+    mix_bits(mix_bits(seed, pixel), index as u64)
+}
+
+/// Element `index` of a random permutation of 0..length picked by `seed`,
+/// computed without storing the permutation (Kensler 2013)
+pub fn permutation_element(mut index: u32, length: u32, seed: u32) -> u32 {
+    let mut mask = length.wrapping_sub(1);
+    mask |= mask >> 1;
+    mask |= mask >> 2;
+    mask |= mask >> 4;
+    mask |= mask >> 8;
+    mask |= mask >> 16;
+
+    loop {
+        index ^= seed;
+        index = index.wrapping_mul(0xe170_893d);
+        index ^= seed >> 16;
+        index ^= (index & mask) >> 4;
+        index ^= seed >> 8;
+        index = index.wrapping_mul(0x0929_eb3f);
+        index ^= seed >> 23;
+        index ^= (index & mask) >> 1;
+        index = index.wrapping_mul(1 | seed >> 27);
+        index = index.wrapping_mul(0x6935_fa69);
+        index ^= (index & mask) >> 11;
+        index = index.wrapping_mul(0x74dc_b303);
+        index ^= (index & mask) >> 2;
+        index = index.wrapping_mul(0x9e50_1cc3);
+        index ^= (index & mask) >> 2;
+        index = index.wrapping_mul(0xc860_a3df);
+        index &= mask;
+        index ^= index >> 5;
+        if index < length {
+            break;
+        }
+    }
+
+    index.wrapping_add(seed) % length
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KINDS: [SamplerKind; 4] = [
+        SamplerKind::Independent,
+        SamplerKind::Stratified,
+        SamplerKind::Halton,
+        SamplerKind::Sobol,
+    ];
+
+    // Mean squared error of estimating the area of a quarter disk (pi / 4)
+    // over a number of pixels, using a 2D sample after some earlier dimensions
+    fn quarter_disk_error(kind: SamplerKind, samples: u32) -> f32 {
+        let mut sampler = kind.build(3, samples);
+        let expected = std::f32::consts::FRAC_PI_4;
+
+        let mut error = 0.0;
+        for pixel in 0..64 {
+            let mut inside = 0;
+            for index in 0..samples {
+                sampler.start_pixel_sample(pixel, 0, index);
+                sampler.next_2d();
+                sampler.next_1d();
+
+                let (x, y) = sampler.next_2d();
+                if x * x + y * y < 1.0 {
+                    inside += 1;
+                }
+            }
+            let estimate = inside as f32 / samples as f32;
+            error += (estimate - expected) * (estimate - expected);
+        }
+
+        error / 64.0
+    }
+
+    #[test]
+    fn test_samples_in_unit_interval() {
+        for kind in KINDS.iter() {
+            let mut sampler = kind.build(11, 16);
+            for index in 0..16 {
+                sampler.start_pixel_sample(3, 5, index);
+                for _ in 0..40 {
+                    let value = sampler.next_1d();
+                    assert!((0.0..1.0).contains(&value), "{:?}: {}", kind, value);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_samples_are_reproducible() {
+        for kind in KINDS.iter() {
+            let mut a = kind.build(5, 16);
+            let mut b = kind.build(5, 16);
+            a.start_pixel_sample(1, 2, 3);
+            b.start_pixel_sample(1, 2, 3);
+
+            for _ in 0..10 {
+                assert_eq!(a.next_2d(), b.next_2d());
+            }
+        }
+    }
+
+    #[test]
+    fn test_low_discrepancy_converges_faster() {
+        let independent = quarter_disk_error(SamplerKind::Independent, 64);
+
+        for kind in KINDS.iter().skip(1) {
+            let error = quarter_disk_error(*kind, 64);
+            assert!(
+                error < 0.5 * independent,
+                "{:?}: {} vs {}",
+                kind,
+                error,
+                independent
+            );
+        }
+    }
+
+    #[test]
+    fn test_permutation_element_is_a_permutation() {
+        for length in [1, 7, 16, 100] {
+            let mut seen = vec![false; length as usize];
+            for index in 0..length {
+                seen[permutation_element(index, length, 12345) as usize] = true;
+            }
+
+            assert!(seen.iter().all(|&seen| seen));
+        }
+    }
+}
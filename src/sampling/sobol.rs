@@ -0,0 +1,105 @@
+use super::{mix_bits, pixel_sample_hash, Sampler};
+
+/// Owen scrambled Sobol points. Every 1D or 2D request is drawn from the
+/// first two Sobol dimensions, with the sample index shuffled and the digits
+/// scrambled by a hash of the pixel and the dimension ("padding"), so any
+/// number of dimensions stays well distributed. Works best with power of two
+/// sample counts.
+pub struct SobolSampler {
+    seed: u64,
+    pixel_hash: u64,
+    index: u32,
+    dimension: u32,
+}
+
+impl SobolSampler {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            pixel_hash: 0,
+            index: 0,
+            dimension: 0,
+        }
+    }
+
+    fn dimension_hash(&mut self) -> u64 {
+        let hash = mix_bits(self.pixel_hash, self.dimension as u64);
+        self.dimension += 1;
+
+        hash
+    }
+}
+
+impl Sampler for SobolSampler {
+    fn start_pixel_sample(&mut self, x: u32, y: u32, index: u32) {
+        self.pixel_hash = pixel_sample_hash(self.seed, x, y, u32::MAX);
+        self.index = index;
+        self.dimension = 0;
+    }
+
+    fn next_1d(&mut self) -> f32 {
+        let hash = self.dimension_hash();
+        let index = nested_uniform_scramble(self.index, hash as u32);
+
+        to_unit_float(nested_uniform_scramble(
+            sobol(index, 0),
+            (hash >> 32) as u32,
+        ))
+    }
+
+    fn next_2d(&mut self) -> (f32, f32) {
+        let hash = self.dimension_hash();
+        self.dimension += 1;
+        let index = nested_uniform_scramble(self.index, hash as u32);
+        let y_seed = mix_bits(hash, 1) as u32;
+
+        (
+            to_unit_float(nested_uniform_scramble(
+                sobol(index, 0),
+                (hash >> 32) as u32,
+            )),
+            to_unit_float(nested_uniform_scramble(sobol(index, 1), y_seed)),
+        )
+    }
+}
+
+/// First two dimensions of the Sobol sequence, as 32-bit fixed point
+pub fn sobol(index: u32, dimension: u32) -> u32 {
+    match dimension {
+        0 => index.reverse_bits(),
+        _ => {
+            let mut direction = 1u32 << 31;
+            let mut value = 0;
+            let mut index = index;
+            while index != 0 {
+                if index & 1 != 0 {
+                    value ^= direction;
+                }
+                index >>= 1;
+                direction ^= direction >> 1;
+            }
+
+            value
+        }
+    }
+}
+
+// Owen scrambling through a hash that only lets bits affect higher ones
+// (Laine & Karras 2011, constants from Burley 2020)
+fn laine_karras_permutation(mut x: u32, seed: u32) -> u32 {
+    x = x.wrapping_add(seed);
+    x ^= x.wrapping_mul(0x6c50_b47c);
+    x ^= x.wrapping_mul(0xb82f_1e52);
+    x ^= x.wrapping_mul(0xc7af_e638);
+    x ^= x.wrapping_mul(0x8d22_f6e6);
+
+    x
+}
+
+pub fn nested_uniform_scramble(x: u32, seed: u32) -> u32 {
+    laine_karras_permutation(x.reverse_bits(), seed).reverse_bits()
+}
+
+fn to_unit_float(x: u32) -> f32 {
+    (x >> 8) as f32 / (1u32 << 24) as f32
+}
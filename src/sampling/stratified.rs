@@ -0,0 +1,69 @@
+use super::{
+    mix_bits, pcg::Pcg32, permutation_element, pixel_sample_hash, Sampler, ONE_MINUS_EPSILON,
+};
+
+/// Jittered samples: every dimension is split into as many strata as there
+/// are samples per pixel, and each sample of a pixel lands in a different
+/// stratum. Strata are shuffled per pixel and dimension, so dimensions do not
+/// correlate with each other.
+pub struct StratifiedSampler {
+    seed: u64,
+    x_strata: u32,
+    y_strata: u32,
+    pixel_hash: u64,
+    index: u32,
+    dimension: u32,
+    rng: Pcg32,
+}
+
+impl StratifiedSampler {
+    pub fn new(seed: u64, samples_per_pixel: u32) -> Self {
+        let x_strata = ((samples_per_pixel as f32).sqrt() as u32).max(1);
+        let y_strata = samples_per_pixel.div_ceil(x_strata).max(1);
+
+        Self {
+            seed,
+            x_strata,
+            y_strata,
+            pixel_hash: 0,
+            index: 0,
+            dimension: 0,
+            rng: Pcg32::new(seed),
+        }
+    }
+
+    fn next_stratum(&mut self, count: u32) -> u32 {
+        let permutation_seed = mix_bits(self.pixel_hash, self.dimension as u64) as u32;
+        permutation_element(self.index % count, count, permutation_seed)
+    }
+}
+
+impl Sampler for StratifiedSampler {
+    fn start_pixel_sample(&mut self, x: u32, y: u32, index: u32) {
+        self.pixel_hash = pixel_sample_hash(self.seed, x, y, u32::MAX);
+        self.index = index;
+        self.dimension = 0;
+        self.rng = Pcg32::new(pixel_sample_hash(self.seed, x, y, index));
+    }
+
+    fn next_1d(&mut self) -> f32 {
+        let count = self.x_strata * self.y_strata;
+        let stratum = self.next_stratum(count);
+        self.dimension += 1;
+
+        ((stratum as f32 + self.rng.next_f32()) / count as f32).min(ONE_MINUS_EPSILON)
+    }
+
+    fn next_2d(&mut self) -> (f32, f32) {
+        let stratum = self.next_stratum(self.x_strata * self.y_strata);
+        self.dimension += 2;
+
+        let x = (stratum % self.x_strata) as f32 + self.rng.next_f32();
+        let y = (stratum / self.x_strata) as f32 + self.rng.next_f32();
+
+        (
+            (x / self.x_strata as f32).min(ONE_MINUS_EPSILON),
+            (y / self.y_strata as f32).min(ONE_MINUS_EPSILON),
+        )
+    }
+}
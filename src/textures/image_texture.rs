@@ -0,0 +1,43 @@
+use crate::{error::RenderError, framebuffer::linear_pixels, rays::Color, vectors::Point3};
+use std::path::Path;
+
+use super::Texture;
+
+/// Texture looked up from an image, with (0, 0) at its bottom left corner
+pub struct ImageTexture {
+    width: u32,
+    height: u32,
+    pixels: Vec<Color>,
+}
+
+impl ImageTexture {
+    pub fn load(path: &dyn AsRef<Path>) -> Result<Self, RenderError> {
+        let image = image::open(path)
+            .map_err(RenderError::Decoding)?
+            .into_rgb8();
+        let pixels = linear_pixels(&image);
+
+        Ok(Self {
+            width: image.width(),
+            height: image.height(),
+            pixels,
+        })
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, u: f32, v: f32, _: &Point3<f32>) -> Color {
+        if self.pixels.is_empty() {
+            return Color::default();
+        }
+
+        // Repeat outside of [0, 1], image rows go top to bottom
+        let u = u.rem_euclid(1.0);
+        let v = 1.0 - v.rem_euclid(1.0);
+
+        let x = ((u * self.width as f32) as u32).min(self.width - 1);
+        let y = ((v * self.height as f32) as u32).min(self.height - 1);
+
+        self.pixels[(y * self.width + x) as usize]
+    }
+}
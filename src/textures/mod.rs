@@ -1,7 +1,15 @@
+pub mod image_texture;
 pub mod solid_color;
+pub mod vertex_color;
 
-use crate::{rays::Color, vectors::Point3};
+use crate::{hit::Hit, rays::Color, vectors::Point3};
 
-pub trait Texture {
+pub trait Texture: Send + Sync {
     fn value(&self, u: f32, v: f32, p: &Point3<f32>) -> Color;
+
+    /// Texture value at a surface hit. Textures that need more than the
+    /// coordinates and point of the hit override this.
+    fn value_at(&self, hit: &Hit) -> Color {
+        self.value(hit.u, hit.v, &hit.point)
+    }
 }
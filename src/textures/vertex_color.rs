@@ -0,0 +1,93 @@
+use crate::{hit::Hit, objects::mesh::MeshData, rays::Color, vectors::Point3};
+use std::sync::Arc;
+
+use super::Texture;
+
+/// Colors of a mesh's vertices, interpolated across the face that was hit.
+/// Shares the `MeshData` of the `TriangleMesh` it is used on, and meshes
+/// without vertex colors get the fallback color.
+pub struct VertexColor {
+    data: Arc<MeshData>,
+    fallback: Color,
+}
+
+impl VertexColor {
+    pub fn new(data: Arc<MeshData>, fallback: Color) -> Self {
+        Self { data, fallback }
+    }
+}
+
+impl Texture for VertexColor {
+    fn value(&self, _: f32, _: f32, _: &Point3<f32>) -> Color {
+        self.fallback
+    }
+
+    fn value_at(&self, hit: &Hit) -> Color {
+        let colors = &self.data.colors;
+        let face = match self.data.indices.get(hit.primitive as usize) {
+            Some(face) if !colors.is_empty() => face,
+            _ => return self.fallback,
+        };
+        let (b1, b2) = hit.barycentrics;
+        let b0 = 1.0 - b1 - b2;
+
+        b0 * colors[face[0] as usize]
+            + b1 * colors[face[1] as usize]
+            + b2 * colors[face[2] as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        hit::Hittable, materials::lambertian::Lambertian, objects::mesh::TriangleMesh, rays::Ray,
+        vectors::Vec3,
+    };
+
+    // Unit square split in two faces, red on the left and blue on the right
+    fn square(uvs: Vec<(f32, f32)>) -> MeshData {
+        let red = Color::new(1.0, 0.0, 0.0);
+        let blue = Color::new(0.0, 0.0, 1.0);
+
+        MeshData {
+            positions: vec![
+                Point3::new(0.0, 0.0, 0.0),
+                Point3::new(1.0, 0.0, 0.0),
+                Point3::new(1.0, 1.0, 0.0),
+                Point3::new(0.0, 1.0, 0.0),
+            ],
+            colors: vec![red, blue, blue, red],
+            uvs,
+            indices: vec![[0, 1, 2], [0, 2, 3]],
+            ..MeshData::default()
+        }
+    }
+
+    fn color_at(data: MeshData, x: f32, y: f32) -> Color {
+        let data = Arc::new(data);
+        let texture = VertexColor::new(data.clone(), Color::default());
+        let material = Arc::new(Lambertian::new(Color::default()));
+        let mesh = TriangleMesh::from_shared(data, material);
+        let ray = Ray::new(Point3::new(x, y, 1.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+
+        texture.value_at(&mesh.hit(&ray, 0.001, f32::INFINITY).unwrap())
+    }
+
+    #[test]
+    fn test_interpolation_on_both_faces() {
+        for &(x, y) in [(0.75, 0.25), (0.25, 0.75)].iter() {
+            let color = color_at(square(Vec::new()), x, y);
+            assert!((color - Color::new(1.0 - x, 0.0, x)).norm() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_with_texture_coordinates() {
+        // Texture coordinates that don't follow the faces at all
+        let uvs = vec![(0.3, 0.9), (0.1, 0.1), (0.7, 0.2), (0.5, 0.5)];
+        let color = color_at(square(uvs), 0.25, 0.75);
+
+        assert!((color - Color::new(0.75, 0.0, 0.25)).norm() < 1e-5);
+    }
+}
@@ -0,0 +1,190 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+pub const DEFAULT_TILE_SIZE: u32 = 32;
+
+/// Order in which tiles are handed out to the render workers.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TileOrder {
+    /// Left to right, top to bottom
+    Scanline,
+    /// Rings around the center of the image, so the subject appears first
+    Spiral,
+    /// Hilbert curve, keeps consecutive tiles close to each other
+    Hilbert,
+}
+
+/// Rectangular region of the image, in image coordinates (origin at the top left).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tile {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Tile {
+    pub fn len(&self) -> usize {
+        (self.width * self.height) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Pixel coordinates covered by the tile, row by row.
+    pub fn pixels(&self) -> impl Iterator<Item = (u32, u32)> {
+        let tile = *self;
+        (tile.y..tile.y + tile.height)
+            .flat_map(move |y| (tile.x..tile.x + tile.width).map(move |x| (x, y)))
+    }
+}
+
+pub fn generate_tiles(width: u32, height: u32, tile_size: u32, order: TileOrder) -> Vec<Tile> {
+    let tile_size = tile_size.max(1);
+    let columns = width.div_ceil(tile_size);
+    let rows = height.div_ceil(tile_size);
+
+    let mut grid: Vec<(u32, u32)> = (0..rows)
+        .flat_map(|row| (0..columns).map(move |column| (column, row)))
+        .collect();
+
+    match order {
+        TileOrder::Scanline => {}
+        TileOrder::Spiral => {
+            let center_x = (columns as f32 - 1.0) / 2.0;
+            let center_y = (rows as f32 - 1.0) / 2.0;
+            let key = |&(column, row): &(u32, u32)| {
+                let dx = column as f32 - center_x;
+                let dy = row as f32 - center_y;
+                let ring = dx.abs().max(dy.abs()).round();
+                (ring, dy.atan2(dx))
+            };
+            grid.sort_by(|a, b| key(a).partial_cmp(&key(b)).unwrap());
+        }
+        TileOrder::Hilbert => {
+            let side = columns.max(rows).next_power_of_two();
+            grid.sort_by_key(|&(column, row)| hilbert_index(side, column, row));
+        }
+    }
+
+    grid.into_iter()
+        .map(|(column, row)| {
+            let x = column * tile_size;
+            let y = row * tile_size;
+            Tile {
+                x,
+                y,
+                width: tile_size.min(width - x),
+                height: tile_size.min(height - y),
+            }
+        })
+        .collect()
+}
+
+// Distance along the Hilbert curve filling a `side` x `side` grid (side is a power of two)
+fn hilbert_index(side: u32, x: u32, y: u32) -> u64 {
+    let (mut x, mut y) = (x, y);
+    let mut d = 0;
+    let mut s = side / 2;
+    while s > 0 {
+        let rx = (x & s > 0) as u32;
+        let ry = (y & s > 0) as u32;
+        d += s as u64 * s as u64 * ((3 * rx) ^ ry) as u64;
+
+        // Rotate the quadrant
+        if ry == 0 {
+            if rx == 1 {
+                x = side - 1 - x;
+                y = side - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        s /= 2;
+    }
+
+    d
+}
+
+/// Work queue shared between the render workers. Each worker takes the next
+/// tile as soon as it is done with the previous one, so faster threads end up
+/// rendering more tiles.
+pub struct TileQueue {
+    tiles: Vec<Tile>,
+    next: AtomicUsize,
+}
+
+impl TileQueue {
+    pub fn new(tiles: Vec<Tile>) -> Self {
+        Self {
+            tiles,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn pop(&self) -> Option<Tile> {
+        let index = self.next.fetch_add(1, Ordering::Relaxed);
+        self.tiles.get(index).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_covers_image(tiles: &[Tile], width: u32, height: u32) {
+        let mut covered = vec![0; (width * height) as usize];
+        for tile in tiles {
+            for (x, y) in tile.pixels() {
+                covered[(y * width + x) as usize] += 1;
+            }
+        }
+
+        assert!(covered.iter().all(|&count| count == 1));
+    }
+
+    #[test]
+    fn test_tiles_cover_image_once() {
+        for order in [TileOrder::Scanline, TileOrder::Spiral, TileOrder::Hilbert] {
+            let tiles = generate_tiles(100, 70, 32, order);
+
+            assert_eq!(tiles.len(), 12);
+            assert_covers_image(&tiles, 100, 70);
+        }
+    }
+
+    #[test]
+    fn test_spiral_starts_at_center() {
+        let tiles = generate_tiles(96, 96, 32, TileOrder::Spiral);
+
+        assert_eq!((tiles[0].x, tiles[0].y), (32, 32));
+    }
+
+    #[test]
+    fn test_hilbert_steps_to_neighbours() {
+        let tiles = generate_tiles(128, 128, 16, TileOrder::Hilbert);
+
+        for pair in tiles.windows(2) {
+            let dx = (pair[0].x as i32 - pair[1].x as i32).abs();
+            let dy = (pair[0].y as i32 - pair[1].y as i32).abs();
+            assert_eq!(dx + dy, 16);
+        }
+    }
+
+    #[test]
+    fn test_queue_hands_out_each_tile_once() {
+        let queue = TileQueue::new(generate_tiles(64, 64, 32, TileOrder::Scanline));
+
+        let popped: Vec<Tile> = std::iter::from_fn(|| queue.pop()).collect();
+
+        assert_eq!(popped.len(), 4);
+        assert_eq!(queue.pop(), None);
+    }
+}
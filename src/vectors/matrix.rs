@@ -0,0 +1,217 @@
+use super::{Point3, Vec3};
+use std::ops::Mul;
+
+/// Row major 4x4 matrix for affine transforms.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Mat4([[f32; 4]; 4]);
+
+impl Mat4 {
+    pub fn new(rows: [[f32; 4]; 4]) -> Self {
+        Self(rows)
+    }
+
+    pub fn identity() -> Self {
+        Self::scaling(Vec3::new(1.0, 1.0, 1.0))
+    }
+
+    pub fn translation(offset: Vec3<f32>) -> Self {
+        Self([
+            [1.0, 0.0, 0.0, offset.x()],
+            [0.0, 1.0, 0.0, offset.y()],
+            [0.0, 0.0, 1.0, offset.z()],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn scaling(factors: Vec3<f32>) -> Self {
+        Self([
+            [factors.x(), 0.0, 0.0, 0.0],
+            [0.0, factors.y(), 0.0, 0.0],
+            [0.0, 0.0, factors.z(), 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Counter-clockwise rotation of `degrees` around `axis`, following the
+    /// right hand rule.
+    pub fn rotation(axis: Vec3<f32>, degrees: f32) -> Self {
+        let axis = axis.unit_vector();
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        let (x, y, z) = (axis.x(), axis.y(), axis.z());
+        let k = 1.0 - cos;
+
+        Self([
+            [
+                cos + x * x * k,
+                x * y * k - z * sin,
+                x * z * k + y * sin,
+                0.0,
+            ],
+            [
+                y * x * k + z * sin,
+                cos + y * y * k,
+                y * z * k - x * sin,
+                0.0,
+            ],
+            [
+                z * x * k - y * sin,
+                z * y * k + x * sin,
+                cos + z * z * k,
+                0.0,
+            ],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn get(&self, row: usize, column: usize) -> f32 {
+        self.0[row][column]
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut rows = [[0.0; 4]; 4];
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = self.0[j][i];
+            }
+        }
+
+        Self(rows)
+    }
+
+    /// Gauss-Jordan elimination with partial pivoting, `None` for singular
+    /// matrices.
+    pub fn inverse(&self) -> Option<Self> {
+        let mut m = self.0;
+        let mut inverse = Self::identity().0;
+        // Relative to each column's size, so uniformly tiny scales still invert
+        let mut tolerances = [0.0f32; 4];
+        for (column, tolerance) in tolerances.iter_mut().enumerate() {
+            *tolerance =
+                m.iter().fold(0.0f32, |max, row| max.max(row[column].abs())) * f32::EPSILON;
+        }
+
+        for column in 0..4 {
+            let pivot = (column..4)
+                .max_by(|&a, &b| m[a][column].abs().total_cmp(&m[b][column].abs()))
+                .unwrap();
+            if m[pivot][column].abs() <= tolerances[column] {
+                return None;
+            }
+            m.swap(column, pivot);
+            inverse.swap(column, pivot);
+
+            let scale = 1.0 / m[column][column];
+            for j in 0..4 {
+                m[column][j] *= scale;
+                inverse[column][j] *= scale;
+            }
+
+            for row in 0..4 {
+                let factor = m[row][column];
+                if row == column || factor == 0.0 {
+                    continue;
+                }
+                for j in 0..4 {
+                    m[row][j] -= factor * m[column][j];
+                    inverse[row][j] -= factor * inverse[column][j];
+                }
+            }
+        }
+
+        Some(Self(inverse))
+    }
+
+    pub fn transform_point(&self, point: &Point3<f32>) -> Point3<f32> {
+        let m = &self.0;
+        let (x, y, z) = (point.x(), point.y(), point.z());
+        let w = m[3][0] * x + m[3][1] * y + m[3][2] * z + m[3][3];
+        let point = Point3::new(
+            m[0][0] * x + m[0][1] * y + m[0][2] * z + m[0][3],
+            m[1][0] * x + m[1][1] * y + m[1][2] * z + m[1][3],
+            m[2][0] * x + m[2][1] * y + m[2][2] * z + m[2][3],
+        );
+
+        if w == 1.0 {
+            point
+        } else {
+            point / w
+        }
+    }
+
+    /// Ignores the translation, for directions and offsets.
+    pub fn transform_vector(&self, vector: &Vec3<f32>) -> Vec3<f32> {
+        let m = &self.0;
+        let (x, y, z) = (vector.x(), vector.y(), vector.z());
+
+        Vec3::new(
+            m[0][0] * x + m[0][1] * y + m[0][2] * z,
+            m[1][0] * x + m[1][1] * y + m[1][2] * z,
+            m[2][0] * x + m[2][1] * y + m[2][2] * z,
+        )
+    }
+}
+
+impl Default for Mat4 {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Mul for Mat4 {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        let mut rows = [[0.0; 4]; 4];
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..4).map(|k| self.0[i][k] * rhs.0[k][j]).sum();
+            }
+        }
+
+        Self(rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(a: &Mat4, b: &Mat4) {
+        for i in 0..4 {
+            for j in 0..4 {
+                assert!(
+                    (a.get(i, j) - b.get(i, j)).abs() < 1e-5,
+                    "{:?} != {:?}",
+                    a,
+                    b
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_inverse() {
+        let m = Mat4::translation(Vec3::new(1.0, -2.0, 3.0))
+            * Mat4::rotation(Vec3::new(1.0, 1.0, 0.0), 30.0)
+            * Mat4::scaling(Vec3::new(2.0, 0.5, 4.0));
+
+        assert_near(&(m * m.inverse().unwrap()), &Mat4::identity());
+        assert_eq!(Mat4::scaling(Vec3::new(1.0, 0.0, 1.0)).inverse(), None);
+    }
+
+    #[test]
+    fn test_inverse_of_small_matrix() {
+        let m = Mat4::rotation(Vec3::new(1.0, 1.0, 0.0), 30.0)
+            * Mat4::scaling(Vec3::new(1e-8, 2e-8, 1e-8));
+
+        assert_near(&(m * m.inverse().unwrap()), &Mat4::identity());
+    }
+
+    #[test]
+    fn test_rotation_is_counter_clockwise() {
+        let rotated = Mat4::rotation(Vec3::new(0.0, 0.0, 1.0), 90.0)
+            .transform_vector(&Vec3::new(1.0, 0.0, 0.0));
+
+        assert!((rotated - Vec3::new(0.0, 1.0, 0.0)).norm() < 1e-6);
+    }
+}
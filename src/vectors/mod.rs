@@ -1,3 +1,6 @@
+pub mod matrix;
+pub mod onb;
+pub mod transform;
 pub mod utils;
 
 use std::ops::{AddAssign, DivAssign, MulAssign, Neg};
@@ -98,4 +101,16 @@ mod tests {
 
         assert_eq!(vec3, Vec3(1.0, 2.0, 3.0));
     }
+
+    #[test]
+    fn test_orthonormal_basis() {
+        for normal in [Vec3(0.0, 0.0, -1.0), Vec3(1.0, 2.0, 3.0).unit_vector()] {
+            let (tangent, bitangent) = normal.orthonormal_basis();
+
+            assert!((tangent.norm() - 1.0).abs() < 1e-6);
+            assert!(tangent.dot(&normal).abs() < 1e-6);
+            assert!(bitangent.dot(&normal).abs() < 1e-6);
+            assert!((tangent.cross(&bitangent) - normal).norm() < 1e-6);
+        }
+    }
 }
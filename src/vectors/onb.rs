@@ -0,0 +1,29 @@
+use super::Vec3;
+
+/// Orthonormal basis with `w` as its main axis, to work in the local frame
+/// of a shape.
+#[derive(Clone, Copy, Debug)]
+pub struct Onb {
+    pub u: Vec3<f32>,
+    pub v: Vec3<f32>,
+    pub w: Vec3<f32>,
+}
+
+impl Onb {
+    pub fn from_w(w: &Vec3<f32>) -> Self {
+        let w = w.unit_vector();
+        let (u, v) = w.orthonormal_basis();
+
+        Self { u, v, w }
+    }
+
+    /// Local coordinates to world space.
+    pub fn local(&self, a: &Vec3<f32>) -> Vec3<f32> {
+        a.x() * self.u + a.y() * self.v + a.z() * self.w
+    }
+
+    /// World space to local coordinates.
+    pub fn to_local(&self, a: &Vec3<f32>) -> Vec3<f32> {
+        Vec3::new(a.dot(&self.u), a.dot(&self.v), a.dot(&self.w))
+    }
+}
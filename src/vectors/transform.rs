@@ -0,0 +1,164 @@
+use super::{matrix::Mat4, Point3, Vec3};
+use crate::aabb::AAAB;
+
+/// An affine transform together with its inverse, built by chaining
+/// `then` calls in the order they are applied.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Transform {
+    matrix: Mat4,
+    inverse: Mat4,
+    // Inverse transpose, kept so normals don't rebuild it on every hit
+    normal_matrix: Mat4,
+}
+
+impl Transform {
+    /// Returns `None` if `matrix` is not invertible.
+    pub fn new(matrix: Mat4) -> Option<Self> {
+        let inverse = matrix.inverse()?;
+
+        Some(Self::from_parts(matrix, inverse))
+    }
+
+    fn from_parts(matrix: Mat4, inverse: Mat4) -> Self {
+        Self {
+            matrix,
+            inverse,
+            normal_matrix: inverse.transpose(),
+        }
+    }
+
+    pub fn identity() -> Self {
+        Self::from_parts(Mat4::identity(), Mat4::identity())
+    }
+
+    pub fn translation(offset: Vec3<f32>) -> Self {
+        Self::from_parts(Mat4::translation(offset), Mat4::translation(-offset))
+    }
+
+    /// Panics on zero factors, which would flatten the object.
+    pub fn scaling(factors: Vec3<f32>) -> Self {
+        assert!(
+            factors.x() != 0.0 && factors.y() != 0.0 && factors.z() != 0.0,
+            "Scale factors must be non-zero"
+        );
+
+        Self::from_parts(
+            Mat4::scaling(factors),
+            Mat4::scaling(Vec3::new(
+                1.0 / factors.x(),
+                1.0 / factors.y(),
+                1.0 / factors.z(),
+            )),
+        )
+    }
+
+    pub fn rotation(axis: Vec3<f32>, degrees: f32) -> Self {
+        let matrix = Mat4::rotation(axis, degrees);
+
+        Self::from_parts(matrix, matrix.transpose())
+    }
+
+    pub fn rotation_x(degrees: f32) -> Self {
+        Self::rotation(Vec3::new(1.0, 0.0, 0.0), degrees)
+    }
+
+    pub fn rotation_y(degrees: f32) -> Self {
+        Self::rotation(Vec3::new(0.0, 1.0, 0.0), degrees)
+    }
+
+    pub fn rotation_z(degrees: f32) -> Self {
+        Self::rotation(Vec3::new(0.0, 0.0, 1.0), degrees)
+    }
+
+    /// Applies `self` first and `next` after it.
+    pub fn then(&self, next: &Transform) -> Self {
+        Self::from_parts(next.matrix * self.matrix, self.inverse * next.inverse)
+    }
+
+    pub fn inverse(&self) -> Self {
+        Self::from_parts(self.inverse, self.matrix)
+    }
+
+    pub fn matrix(&self) -> &Mat4 {
+        &self.matrix
+    }
+
+    pub fn transform_point(&self, point: &Point3<f32>) -> Point3<f32> {
+        self.matrix.transform_point(point)
+    }
+
+    pub fn transform_vector(&self, vector: &Vec3<f32>) -> Vec3<f32> {
+        self.matrix.transform_vector(vector)
+    }
+
+    /// Same as `inverse().transform_point`, without building the inverse.
+    pub fn inverse_transform_point(&self, point: &Point3<f32>) -> Point3<f32> {
+        self.inverse.transform_point(point)
+    }
+
+    pub fn inverse_transform_vector(&self, vector: &Vec3<f32>) -> Vec3<f32> {
+        self.inverse.transform_vector(vector)
+    }
+
+    /// Normals go through the inverse transpose, so they stay perpendicular
+    /// to the surface under non-uniform scaling. The result is not normalized.
+    pub fn transform_normal(&self, normal: &Vec3<f32>) -> Vec3<f32> {
+        self.normal_matrix.transform_vector(normal)
+    }
+
+    /// Box around the 8 transformed corners of `b_box`.
+    pub fn transform_b_box(&self, b_box: &AAAB) -> AAAB {
+        let (min, max) = (b_box.min(), b_box.max());
+        let mut corners = [Point3::default(); 8];
+        for (i, corner) in corners.iter_mut().enumerate() {
+            let pick = |bit: usize, axis: u32| {
+                if i & bit == 0 {
+                    min.get(axis).unwrap()
+                } else {
+                    max.get(axis).unwrap()
+                }
+            };
+            *corner = self.transform_point(&Point3::new(pick(1, 0), pick(2, 1), pick(4, 2)));
+        }
+
+        AAAB::surrounding_points(&corners)
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_then_applies_in_order() {
+        let transform = Transform::scaling(Vec3::new(2.0, 2.0, 2.0))
+            .then(&Transform::translation(Vec3::new(1.0, 0.0, 0.0)));
+        let point = transform.transform_point(&Point3::new(1.0, 1.0, 1.0));
+
+        assert_eq!(point, Point3::new(3.0, 2.0, 2.0));
+        assert_eq!(
+            transform.inverse().transform_point(&point),
+            Point3::new(1.0, 1.0, 1.0)
+        );
+        assert_eq!(
+            transform.inverse_transform_point(&point),
+            Point3::new(1.0, 1.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn test_normals_stay_perpendicular() {
+        let transform = Transform::scaling(Vec3::new(4.0, 1.0, 1.0));
+        // Surface x + y = 1 becomes x / 4 + y = 1
+        let tangent = transform.transform_vector(&Vec3::new(1.0, -1.0, 0.0));
+        let normal = transform.transform_normal(&Vec3::new(1.0, 1.0, 0.0));
+
+        assert!(tangent.dot(&normal).abs() < 1e-6);
+    }
+}
@@ -1,7 +1,7 @@
-use rand::{prelude::Rng, thread_rng};
-
 use super::Vec3;
+use crate::sampling::Sampler;
 use std::{
+    f32::consts::{FRAC_PI_2, FRAC_PI_4, PI},
     fmt::Display,
     ops::{Add, Div, Mul, Sub},
 };
@@ -79,44 +79,72 @@ impl Vec3<f32> {
         )
     }
 
-    pub fn new_random(min: f32, max: f32) -> Self {
-        let mut rng = thread_rng();
+    /// Two unit vectors completing `self` (a unit vector) into an orthonormal
+    /// basis, using the branchless construction of Duff et al.
+    pub fn orthonormal_basis(&self) -> (Self, Self) {
+        let sign = 1.0f32.copysign(self.z());
+        let a = -1.0 / (sign + self.z());
+        let b = self.x() * self.y() * a;
+
+        (
+            Self::new(
+                1.0 + sign * self.x() * self.x() * a,
+                sign * b,
+                -sign * self.x(),
+            ),
+            Self::new(b, sign + self.y() * self.y() * a, -self.y()),
+        )
+    }
+
+    pub fn new_random(sampler: &mut dyn Sampler, min: f32, max: f32) -> Self {
+        let range = max - min;
         Self::new(
-            rng.gen_range(min..max),
-            rng.gen_range(min..max),
-            rng.gen_range(min..max),
+            min + range * sampler.next_1d(),
+            min + range * sampler.next_1d(),
+            min + range * sampler.next_1d(),
         )
     }
 
-    pub fn random_in_unit_sphere() -> Self {
-        loop {
-            let p = Vec3::new_random(-1.0, 1.0);
-            if p.norm_sqr() < 1.0 {
-                return p;
-            }
-        }
+    pub fn random_in_unit_sphere(sampler: &mut dyn Sampler) -> Self {
+        // Cube root keeps the points uniformly distributed over the volume
+        let radius = sampler.next_1d().cbrt();
+        radius * Self::random_unit_vector(sampler)
     }
 
-    pub fn random_unit_vector() -> Self {
-        Self::random_in_unit_sphere().unit_vector()
+    pub fn random_unit_vector(sampler: &mut dyn Sampler) -> Self {
+        // Maps a 2D sample to the sphere without rejection, so low-discrepancy
+        // samples keep their distribution
+        let (u1, u2) = sampler.next_2d();
+        let z = 1.0 - 2.0 * u1;
+        let r = (1.0 - z * z).max(0.0).sqrt();
+        let phi = 2.0 * PI * u2;
+
+        Self::new(r * phi.cos(), r * phi.sin(), z)
     }
 
-    pub fn random_in_hemisphere(normal: &Vec3<f32>) -> Self {
-        let in_unit_sphere = Self::random_in_unit_sphere();
+    pub fn random_in_hemisphere(sampler: &mut dyn Sampler, normal: &Vec3<f32>) -> Self {
+        let in_unit_sphere = Self::random_in_unit_sphere(sampler);
         if normal.dot(&in_unit_sphere) > 0.0 {
             return in_unit_sphere;
         }
         -in_unit_sphere
     }
 
-    pub fn random_in_unit_disk() -> Self {
-        let mut rng = thread_rng();
-        loop {
-            let p = Vec3::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), 0.0);
-            if p.norm_sqr() < 1.0 {
-                return p;
-            }
+    pub fn random_in_unit_disk(sampler: &mut dyn Sampler) -> Self {
+        // Shirley-Chiu concentric mapping from the unit square
+        let (u1, u2) = sampler.next_2d();
+        let (a, b) = (2.0 * u1 - 1.0, 2.0 * u2 - 1.0);
+        if a == 0.0 && b == 0.0 {
+            return Self::default();
         }
+
+        let (r, theta) = if a.abs() > b.abs() {
+            (a, FRAC_PI_4 * (b / a))
+        } else {
+            (b, FRAC_PI_2 - FRAC_PI_4 * (a / b))
+        };
+
+        Self::new(r * theta.cos(), r * theta.sin(), 0.0)
     }
 
     pub fn is_near_zero(&self) -> bool {